@@ -1,59 +1,66 @@
-use anyhow::Result;
-use image::{ImageBuffer, Rgb};
-use mupdf::{Document, Matrix, Pixmap, Colorspace, Device};
-
-const SAMPLE_DRAW_PDF: &str = "test 6.pdf";
-const DPI: f32 = 150.0;
-
-fn pdf_page_to_image(doc: &Document, page_num: i32, dpi: f32) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>> {
-    let page = doc.load_page(page_num)?;
-    let scale = dpi / 72.0;
-    let transform = Matrix::new_scale(scale, scale);
-    let bounds = page.bounds()?;
-    let width = ((bounds.x1 - bounds.x0) * scale) as i32;
-    let height = ((bounds.y1 - bounds.y0) * scale) as i32;
-
-    let mut pixmap = Pixmap::new_with_w_h(
-        &Colorspace::device_gray(),
-        width,
-        height,
-        false
-    )?;
-    pixmap.clear()?;
-
-    let device = Device::from_pixmap(&pixmap)?;
-    page.run(&device, &transform)?;
-
-    let mut img = ImageBuffer::new(width as u32, height as u32);
-    let samples = pixmap.samples();
-
-    for y in 0..height {
-        for x in 0..width {
-            let idx = (y * width + x) as usize;
-            if idx < samples.len() {
-                let gray = samples[idx];
-                let value = if gray < 160 { 0 } else { 255 };
-                img.put_pixel(x as u32, y as u32, Rgb([value, value, value]));
+use std::collections::VecDeque;
+
+use anyhow::{Context, Result};
+use mupdf::Document;
+use ocr_app::{pdf_page_to_image, RenderOptions};
+
+/// Extraction favours speed over OCR quality, so it renders at a lower DPI by default
+const DEFAULT_DPI: f32 = 150.0;
+
+struct Args {
+    pdf_path: String,
+    render: RenderOptions,
+}
+
+fn parse_args() -> Result<Args, lexopt::Error> {
+    use lexopt::prelude::*;
+
+    let mut values = VecDeque::new();
+    let mut render = RenderOptions { dpi: DEFAULT_DPI, ..RenderOptions::default() };
+    let mut parser = lexopt::Parser::from_env();
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Value(val) => values.push_back(val.string()?),
+            Long("binarization") => render.binarization = parser.value()?.parse()?,
+            Long("dpi") => render.dpi = parser.value()?.parse()?,
+            Long("pages") => {
+                render.pages = ocr_app::render::parse_page_ranges(&parser.value()?.string()?)
+                    .map_err(|e| lexopt::Error::Custom(e.into()))?;
+            }
+            Long("max-pixels") => render.max_pixels = Some(parser.value()?.parse()?),
+            Long("help") => {
+                println!(
+                    "Usage: {bin_name} [--binarization <otsu|sauvola|none>] [--dpi <dpi>] [--pages <1-3,7>] [--max-pixels <n>] <pdf_file>",
+                    bin_name = parser.bin_name().unwrap_or("extract_pdf_pages")
+                );
+                std::process::exit(0);
             }
+            _ => return Err(arg.unexpected()),
         }
     }
 
-    Ok(img)
+    let pdf_path = values.pop_front().ok_or("missing PDF file path")?;
+
+    Ok(Args { pdf_path, render })
 }
 
 fn main() -> Result<()> {
+    let args = parse_args()?;
+    args.render.validate()?;
 
-
-    let doc = Document::open(SAMPLE_DRAW_PDF)?;
+    let doc = Document::open(&args.pdf_path)
+        .context("Failed to open PDF file")?;
     let page_count = doc.page_count()?;
 
     println!("Found {} pages in PDF", page_count);
-    
-    for page_num in 0..page_count {
+
+    for page_index in args.render.selected_pages(page_count as usize)? {
+        let page_num = page_index as i32;
         println!("Processing page {}", page_num + 1);
-        let img = pdf_page_to_image(&doc, page_num, DPI)?;
-        
-        let output_path = format!("page_{}.png", (page_num+60) + 1);
+        let img = pdf_page_to_image(&doc, page_num, &args.render)?;
+
+        let output_path = format!("page_{}.png", page_num + 1);
         img.save(&output_path)?;
         println!("Saved {}", output_path);
     }
@@ -2,12 +2,14 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::io::Cursor;
 use axum::extract::DefaultBodyLimit;
+use axum::extract::multipart::Field;
 use sha2::{Sha256, Digest};
 
 use anyhow::{Context, Result};
 use axum::{
     extract::{Multipart, State},
-    response::{Html, Json},
+    http::StatusCode,
+    response::{Html, IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -16,8 +18,10 @@ use tower_http::services::ServeDir;
 use tempfile::NamedTempFile;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
-use ocr_app::OcrResult;
+use ocr_app::{LabelGrammar, OcrResult, RenderOptions, RevisionView, SpecSource};
+use ocr_app::crosscheck::CrossCheckReport;
 
+/// Legacy label flags still sent by older clients; converted to a LabelGrammar
 #[derive(serde::Deserialize)]
 struct LabelOptions {
     allow_2: bool,
@@ -31,6 +35,7 @@ struct LabelOptions {
 struct ProcessResponse {
     pages: Vec<PageResult>,
     file_hash: String,
+    low_confidence: f32,  // Results below this confidence are marked uncertain
 }
 
 #[derive(serde::Serialize)]
@@ -41,8 +46,16 @@ struct DocxProcessResponse {
     file_hash: String,
 }
 
+#[derive(serde::Serialize)]
+struct CrossCheckResponse {
+    report: CrossCheckReport,
+    pdf_hash: String,
+    docx_hash: String,
+}
+
 #[derive(serde::Serialize)]
 struct PageResult {
+    page: usize,    // 1-based page number in the PDF, or sheet number across the drawing files
     image: String,  // Base64 encoded image
     ocr_results: Vec<OcrResult>,
 }
@@ -58,6 +71,28 @@ struct AppState {
     engine: OcrEngine,
 }
 
+/// A failed request: the status to answer with and a message for the client
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(message: impl std::fmt::Display) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message.to_string())
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        println!("[DEBUG] Request failed ({}): {}", self.0, self.1);
+        (self.0, self.1).into_response()
+    }
+}
+
 async fn comparison_view() -> Html<String> {
     let template_path = "templates/comparison.html";
     match tokio::fs::read_to_string(template_path).await {
@@ -73,10 +108,8 @@ async fn index() -> Html<String> {
     println!("[DEBUG] Current working directory: {}", std::env::current_dir().unwrap().display());
     println!("[DEBUG] Directory contents:");
     if let Ok(entries) = std::fs::read_dir("templates") {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                println!("[DEBUG] - {}", entry.path().display());
-            }
+        for entry in entries.flatten() {
+            println!("[DEBUG] - {}", entry.path().display());
         }
     } else {
         println!("[DEBUG] Could not read /app/templates directory");
@@ -94,15 +127,76 @@ async fn index() -> Html<String> {
     }
 }
 
+/// Read a label grammar from whichever form field carries it: the legacy
+/// `label_options` flags, an inline `label_grammar`, or a named `label_profile`
+async fn read_label_grammar(field: Field<'_>) -> Result<LabelGrammar, String> {
+    let name = field.name().unwrap_or("").to_string();
+    let text = field
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+
+    match name.as_str() {
+        "label_options" => {
+            let options: LabelOptions = serde_json::from_str(&text)
+                .map_err(|e| format!("Failed to parse label options: {}", e))?;
+            Ok(LabelGrammar::from_flags(options.allow_2, options.allow_3, options.allow_4, options.allow_letters, options.allow_hyphen))
+        }
+        "label_grammar" => serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse label grammar: {}", e)),
+        _ => {
+            // Profiles are looked up by name only, never by path
+            if text.is_empty() || !text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err(format!("Invalid label profile name: {}", text));
+            }
+            LabelGrammar::from_toml_file(format!("profiles/{}.toml", text))
+                .map_err(|e| format!("Failed to load label profile: {}", e))
+        }
+    }
+}
+
+/// Apply one of the render form fields: `dpi`, `pages` (e.g. "1-3,7"), `max_pixels`,
+/// `binarization`, `deskew` ("true" or "false"), `tile_size`, `tile_overlap` or `text_layer` ("true" or "false")
+async fn read_render_option(field: Field<'_>, options: &mut RenderOptions) -> Result<(), ApiError> {
+    let name = field.name().unwrap_or("").to_string();
+    let text = field
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    let text = text.trim();
+
+    let invalid = |e: &dyn std::fmt::Display| ApiError::bad_request(format!("Invalid {} '{}': {}", name, text, e));
+    match name.as_str() {
+        "dpi" => options.dpi = text.parse().map_err(|e| invalid(&e))?,
+        "pages" => options.pages = ocr_app::render::parse_page_ranges(text).map_err(|e| invalid(&e))?,
+        "max_pixels" => options.max_pixels = Some(text.parse().map_err(|e| invalid(&e))?),
+        "deskew" => options.deskew = text.parse().map_err(|e| invalid(&e))?,
+        "tile_size" => options.tile_size = Some(text.parse().map_err(|e| invalid(&e))?),
+        "tile_overlap" => options.tile_overlap = text.parse().map_err(|e| invalid(&e))?,
+        "text_layer" => options.text_layer = text.parse().map_err(|e| invalid(&e))?,
+        _ => options.binarization = text.parse().map_err(|e: anyhow::Error| invalid(&e))?,
+    }
+    Ok(())
+}
+
+/// Read which version of a specification with tracked changes to check
+async fn read_revision_view(field: Field<'_>) -> Result<RevisionView, String> {
+    let text = field
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read revisions: {}", e))?;
+    text.trim().parse().map_err(|e: anyhow::Error| e.to_string())
+}
+
 async fn process_docx(
-    State(state): State<Arc<AppState>>,
+    State(_state): State<Arc<AppState>>,
     mut multipart: Multipart,
-) -> Result<Json<DocxProcessResponse>, String> {
-    println!("[DEBUG] Starting DOCX processing");
-    // Get the DOCX file from the form data
-    // Get the DOCX file
+) -> Result<Json<DocxProcessResponse>, ApiError> {
+    println!("[DEBUG] Starting specification processing");
+    // Get the specification file from the form data, in any format `SpecSource` reads
     let mut docx_data = None;
-    let mut label_options: Option<LabelOptions> = None;
+    let mut grammar = LabelGrammar::default();
+    let mut revisions = RevisionView::default();
 
     while let Some(field) = multipart
         .next_field()
@@ -110,29 +204,22 @@ async fn process_docx(
         .map_err(|e| format!("Failed to get form field: {}", e))? 
     {
         match field.name() {
-            Some("docx") => {
+            Some("spec" | "docx") => {
                 docx_data = Some(
                     field.bytes()
                         .await
                         .map_err(|e| format!("Failed to read file data: {}", e))?
                 );
             }
-            Some("label_options") => {
-                let options_str = field
-                    .text()
-                    .await
-                    .map_err(|e| format!("Failed to read label options: {}", e))?;
-                label_options = Some(
-                    serde_json::from_str(&options_str)
-                        .map_err(|e| format!("Failed to parse label options: {}", e))?
-                );
+            Some("label_options" | "label_grammar" | "label_profile") => {
+                grammar = read_label_grammar(field).await.map_err(ApiError::bad_request)?;
             }
+            Some("revisions") => revisions = read_revision_view(field).await.map_err(ApiError::bad_request)?,
             _ => continue,
         }
     }
 
-    let data = docx_data.ok_or_else(|| "No DOCX file provided".to_string())?;
-    let options = label_options.ok_or_else(|| "No label options provided".to_string())?;
+    let data = docx_data.ok_or_else(|| ApiError::bad_request("No specification file provided"))?;
 
     // Calculate SHA-256 hash
     let mut hasher = Sha256::new();
@@ -149,13 +236,15 @@ async fn process_docx(
 
     let file_path = temp_file.path();
 
-    // Process the DOCX
-    println!("[DEBUG] Processing DOCX file: {}", file_path.display());
-    let results = match ocr_app::process_docx(&state.engine, file_path, options.allow_2, options.allow_3, options.allow_4, options.allow_letters, options.allow_hyphen) {
+    // Process the specification
+    println!("[DEBUG] Processing specification file: {}", file_path.display());
+    let source = SpecSource::detect(file_path)
+        .map_err(|e| ApiError::bad_request(format!("Failed to read specification: {}", e)))?;
+    let results = match ocr_app::process_spec(&source, &grammar, revisions) {
         Ok(r) => r,
         Err(e) => {
-            println!("[DEBUG] DOCX processing error: {}", e);
-            return Err(format!("Failed to process DOCX: {}", e));
+            println!("[DEBUG] {} processing error: {}", source.format_name(), e);
+            return Err(ApiError::bad_request(format!("Failed to process {}: {}", source.format_name(), e)));
         }
     };
 
@@ -182,28 +271,40 @@ async fn process_docx(
 async fn process_pdf(
     State(state): State<Arc<AppState>>,
     mut multipart: Multipart,
-) -> Result<Json<ProcessResponse>, String> {
+) -> Result<Json<ProcessResponse>, ApiError> {
     println!("[DEBUG] Starting PDF processing");
-    // Get the PDF file from the form data
-    let field = multipart
+    // Get the PDF file, label grammar and render options from the form data
+    let mut pdf_data = None;
+    let mut grammar = LabelGrammar::default();
+    let mut render_options = RenderOptions::default();
+
+    while let Some(field) = multipart
         .next_field()
         .await
-        .map_err(|e| format!("Failed to get form field: {}", e))?;
-
-    if field.is_none() {
-        return Err("No file provided".to_string());
+        .map_err(|e| format!("Failed to get form field: {}", e))?
+    {
+        match field.name() {
+            Some("pdf") => {
+                pdf_data = Some(
+                    field.bytes()
+                        .await
+                        .map_err(|e| format!("Failed to read file data: {}", e))?
+                );
+            }
+            Some("label_options" | "label_grammar" | "label_profile") => {
+                grammar = read_label_grammar(field).await.map_err(ApiError::bad_request)?;
+            }
+            Some("dpi" | "pages" | "max_pixels" | "binarization" | "deskew" | "tile_size" | "tile_overlap" | "text_layer") => {
+                read_render_option(field, &mut render_options).await?;
+            }
+            _ => continue,
+        }
     }
 
-    let field = field.unwrap();
-    if field.name() != Some("pdf") {
-        return Err("Invalid form field name".to_string());
-    }
+    // Out-of-range values would make the render or OCR arbitrarily expensive
+    render_options.validate().map_err(ApiError::bad_request)?;
 
-    // Read the file data
-    let data = field
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read file data: {}", e))?;
+    let data = pdf_data.ok_or_else(|| ApiError::bad_request("No file provided"))?;
 
     // Calculate SHA-256 hash
     let mut hasher = Sha256::new();
@@ -222,16 +323,25 @@ async fn process_pdf(
 
     // Process the PDF
     println!("[DEBUG] Processing PDF file: {}", file_path.display());
-    let results = match ocr_app::process_pdf(&state.engine, file_path) {
+    let results = match ocr_app::process_pdf(&state.engine, file_path, &grammar, &render_options) {
         Ok(r) => r,
         Err(e) => {
             println!("[DEBUG] PDF processing error: {}", e);
-            return Err(format!("Failed to process PDF: {}", e));
+            return Err(format!("Failed to process PDF: {}", e).into());
         }
     };
 
-    // Convert results to response format
-    let pages = results.into_iter().map(|(img, ocr_results)| {
+    // Return the results
+    Ok(Json(ProcessResponse { 
+        pages: page_results(results)?,
+        file_hash: hash,
+        low_confidence: ocr_app::LOW_CONFIDENCE,
+    }))
+}
+
+/// Convert OCR'd pages to the response format, with each image as a base64 PNG
+fn page_results(results: Vec<(usize, image::RgbImage, Vec<OcrResult>)>) -> Result<Vec<PageResult>, String> {
+    results.into_iter().map(|(page, img, ocr_results)| {
         // Convert image to base64
         let mut img_data = Vec::new();
         img.write_to(&mut Cursor::new(&mut img_data), image::ImageOutputFormat::Png)
@@ -239,15 +349,161 @@ async fn process_pdf(
         let img_base64 = STANDARD.encode(&img_data);
 
         Ok(PageResult {
+            page,
             image: format!("data:image/png;base64,{}", img_base64),
             ocr_results,
         })
-    }).collect::<Result<Vec<_>, String>>()?;
+    }).collect()
+}
 
-    // Return the results
-    Ok(Json(ProcessResponse { 
-        pages,
-        file_hash: hash
+/// OCR drawing sheets uploaded as images: one or more `drawings` fields holding TIFF
+/// (single or multi-page), PNG or JPEG files, with the same grammar and render fields as `/process-pdf`
+async fn process_drawings(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> Result<Json<ProcessResponse>, ApiError> {
+    println!("[DEBUG] Starting drawing sheet processing");
+    let mut drawings = Vec::new();
+    let mut grammar = LabelGrammar::default();
+    let mut render_options = RenderOptions::default();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| format!("Failed to get form field: {}", e))?
+    {
+        match field.name() {
+            Some("drawings") => {
+                drawings.push(
+                    field.bytes()
+                        .await
+                        .map_err(|e| format!("Failed to read file data: {}", e))?
+                );
+            }
+            Some("label_options" | "label_grammar" | "label_profile") => {
+                grammar = read_label_grammar(field).await.map_err(ApiError::bad_request)?;
+            }
+            Some("dpi" | "pages" | "max_pixels" | "binarization" | "deskew" | "tile_size" | "tile_overlap" | "text_layer") => {
+                read_render_option(field, &mut render_options).await?;
+            }
+            _ => continue,
+        }
+    }
+
+    // Out-of-range values would make the render or OCR arbitrarily expensive
+    render_options.validate().map_err(ApiError::bad_request)?;
+
+    if drawings.is_empty() {
+        return Err(ApiError::bad_request("No file provided"));
+    }
+
+    // The hash covers every file in upload order
+    let mut hasher = Sha256::new();
+    let mut temp_files = Vec::new();
+    for data in &drawings {
+        let (temp_file, file_hash) = write_temp_file(data)?;
+        hasher.update(file_hash.as_bytes());
+        temp_files.push(temp_file);
+    }
+    let hash = format!("{:x}", hasher.finalize());
+
+    let paths: Vec<&std::path::Path> = temp_files.iter().map(|file| file.path()).collect();
+    println!("[DEBUG] Processing {} drawing files", paths.len());
+    let results = match ocr_app::process_images(&state.engine, &paths, &grammar, &render_options) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("[DEBUG] Drawing processing error: {}", e);
+            return Err(format!("Failed to process drawings: {}", e).into());
+        }
+    };
+
+    Ok(Json(ProcessResponse {
+        pages: page_results(results)?,
+        file_hash: hash,
+        low_confidence: ocr_app::LOW_CONFIDENCE,
+    }))
+}
+
+/// Hash uploaded bytes and write them to a temporary file for the library to read
+fn write_temp_file(data: &[u8]) -> Result<(NamedTempFile, String), String> {
+    // Calculate SHA-256 hash
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    let hash = format!("{:x}", hasher.finalize());
+
+    let temp_file = NamedTempFile::new()
+        .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+    std::io::Write::write_all(&mut temp_file.as_file(), data)
+        .map_err(|e| format!("Failed to write to temporary file: {}", e))?;
+
+    Ok((temp_file, hash))
+}
+
+async fn cross_check(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> Result<Json<CrossCheckResponse>, ApiError> {
+    println!("[DEBUG] Starting cross-check");
+    let mut pdf_data = None;
+    let mut docx_data = None;
+    let mut grammar = LabelGrammar::default();
+    let mut revisions = RevisionView::default();
+    let mut render_options = RenderOptions::default();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| format!("Failed to get form field: {}", e))?
+    {
+        match field.name() {
+            Some("pdf") => {
+                pdf_data = Some(
+                    field.bytes()
+                        .await
+                        .map_err(|e| format!("Failed to read PDF data: {}", e))?
+                );
+            }
+            Some("spec" | "docx") => {
+                docx_data = Some(
+                    field.bytes()
+                        .await
+                        .map_err(|e| format!("Failed to read specification data: {}", e))?
+                );
+            }
+            Some("label_options" | "label_grammar" | "label_profile") => {
+                grammar = read_label_grammar(field).await.map_err(ApiError::bad_request)?;
+            }
+            Some("revisions") => revisions = read_revision_view(field).await.map_err(ApiError::bad_request)?,
+            Some("dpi" | "pages" | "max_pixels" | "binarization" | "deskew" | "tile_size" | "tile_overlap" | "text_layer") => {
+                read_render_option(field, &mut render_options).await?;
+            }
+            _ => continue,
+        }
+    }
+
+    // Out-of-range values would make the render or OCR arbitrarily expensive
+    render_options.validate().map_err(ApiError::bad_request)?;
+
+    let pdf_data = pdf_data.ok_or_else(|| ApiError::bad_request("No PDF file provided"))?;
+    let docx_data = docx_data.ok_or_else(|| ApiError::bad_request("No specification file provided"))?;
+
+    let (pdf_file, pdf_hash) = write_temp_file(&pdf_data)?;
+    let (docx_file, docx_hash) = write_temp_file(&docx_data)?;
+
+    let source = SpecSource::detect(docx_file.path())
+        .map_err(|e| ApiError::bad_request(format!("Failed to read specification: {}", e)))?;
+    let docx_results = ocr_app::process_spec(&source, &grammar, revisions)
+        .map_err(|e| ApiError::bad_request(format!("Failed to process {}: {}", source.format_name(), e)))?;
+    let pdf_results = ocr_app::process_pdf(&state.engine, pdf_file.path(), &grammar, &render_options)
+        .map_err(|e| format!("Failed to process PDF: {}", e))?;
+
+    let report = ocr_app::crosscheck::cross_check(&pdf_results, &docx_results, &grammar)
+        .map_err(|e| format!("Failed to cross-check: {}", e))?;
+
+    Ok(Json(CrossCheckResponse {
+        report,
+        pdf_hash,
+        docx_hash,
     }))
 }
 
@@ -279,12 +535,15 @@ async fn main() -> Result<()> {
     let app = Router::new()
         .route("/", get(index))
         .route("/process-pdf", post(process_pdf))
+        .route("/process-drawings", post(process_drawings))
         .route("/process-docx", post(process_docx))
+        .route("/process-spec", post(process_docx))
+        .route("/cross-check", post(cross_check))
         .route("/comparison", get(comparison_view))
         .nest_service("/static", ServeDir::new("static"))
         .layer(DefaultBodyLimit::max(50 * 1024 * 1024))  // 50MB limit
         .with_state(state);
-    println!("[DEBUG] Router configured with routes: /, /process-pdf, /process-docx, /static");
+    println!("[DEBUG] Router configured with routes: /, /process-pdf, /process-drawings, /process-docx, /process-spec, /cross-check, /static");
 
     // Start server
     let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string()).parse::<u16>().unwrap();
@@ -0,0 +1,630 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use crate::figures::is_heading;
+use crate::{elements, normalize_text, SpecElement};
+
+/// Headings that open the claims, compared without a trailing colon
+static CLAIMS_HEADINGS: &[&str] = &[
+    "CLAIMS", "THE CLAIMS", "WHAT IS CLAIMED IS", "WHAT IS CLAIMED", "WHAT IS CLAIMED IS THE FOLLOWING",
+    "THE INVENTION CLAIMED IS", "WE CLAIM", "I CLAIM",
+];
+
+/// The start of a numbered claim, e.g. "1. A device comprising:" or "Claim 2) The device ..."
+static CLAIM_START_PATTERN: &str = r"(?is)^(?:claim\s+)?([0-9]{1,3})\s*[.):]\s*(\S.*)$";
+
+/// A reference to earlier claims, e.g. "of claim 3" or "according to any of claims 1 to 4"
+static CLAIM_REFERENCE_PATTERN: &str = r"(?i)\bclaims?\s+([0-9]{1,3}(?:\s*(?:,|-|–|\bor\b|\band\b|\bto\b|\bthrough\b)\s*(?:claims?\s+)?[0-9]{1,3})*)";
+
+/// A reference to every earlier claim, e.g. "according to any preceding claim"
+static PRECEDING_CLAIMS_PATTERN: &str = r"(?i)\b(?:preceding|previous|foregoing)\s+claims?\b";
+
+/// Nouns that count a claimed element rather than name it, e.g. "a plurality of springs"
+static COLLECTIVE_NOUNS: &[&str] = &["plurality", "pair", "set", "series", "number", "group"];
+
+/// Words that count the elements a claim introduces, e.g. "two arms"
+static NUMBER_WORDS: &[&str] = &["two", "three", "four", "five", "six", "several", "multiple"];
+
+/// Ordinals that name several like elements at once, e.g. "first and second arms"
+static ORDINALS: &[&str] = &["first", "second", "third", "fourth", "fifth", "sixth"];
+
+/// Words after which a claim may list elements without an article, e.g. "comprising: springs"
+static TRANSITIONS: &[&str] = &["comprising", "comprises", "including", "includes", "having", "has"];
+
+/// Words after "the" that need no antecedent, e.g. "the same" or "the group consisting of"
+static NON_ELEMENTS: &[&str] = &["same", "like", "following", "group", "art", "invention", "step", "steps", "extent", "whole", "rest"];
+
+/// An element name used in a claim
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ClaimTerm {
+    pub name: String,           // Normalized element name, e.g. "support arm"
+    pub text: String,           // The term as written in the claim, e.g. "support arms"
+    pub numerals: Vec<String>,  // Description numerals for the name; empty when the description never numbers it
+}
+
+/// One claim of the CLAIMS section
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct Claim {
+    pub number: usize,
+    pub text: String,             // Full claim text without its number
+    pub depends_on: Vec<usize>,   // Claims it refers back to; empty for an independent claim
+    pub terms: Vec<ClaimTerm>,    // Element names in order of first use
+}
+
+/// A claim term together with every claim that uses it
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ClaimTermReference {
+    pub name: String,
+    pub claims: Vec<usize>,     // Numbers of the claims that use it
+    pub texts: Vec<String>,     // The term as written in those claims
+    pub numerals: Vec<String>,  // Description numerals for the name
+}
+
+/// A problem with the antecedent basis of a claim term
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AntecedentIssue {
+    /// "the X" or "said X" with no "a X" before it in the claim or the claims it depends on
+    MissingAntecedent { claim: usize, term: String, text: String },
+    /// "the X" for an element introduced under another name, e.g. "the support member" after "a support arm"
+    InconsistentName { claim: usize, term: String, text: String, introduced_as: Vec<String>, introduced_in: Vec<usize> },
+}
+
+/// Where an element name starts after its introduction or a reference back to it, and the
+/// ordinals that name several like elements at once, e.g. ["first", "second"] for "first and second arms"
+struct NameStart {
+    start: usize,
+    ordinals: Vec<String>,
+}
+
+/// An element a claim introduces with "a" or "an"
+#[derive(Clone, Debug)]
+struct Introduction {
+    words: Vec<String>,  // Singularized words of the name, e.g. ["support", "arm"]
+    text: String,        // The name as written, e.g. "support arms"
+    claim: usize,
+}
+
+/// Whether a paragraph opens the claims, e.g. "CLAIMS" or "What is claimed is:"
+fn is_claims_heading(paragraph: &str) -> bool {
+    let upper = paragraph.trim().trim_end_matches([':', '.']).trim_end().to_uppercase();
+    CLAIMS_HEADINGS.contains(&upper.as_str())
+}
+
+/// Paragraphs of the claims section, from its heading up to the next heading, e.g. ABSTRACT
+pub fn claims_section(paragraphs: &[String]) -> Option<std::ops::Range<usize>> {
+    let start = paragraphs.iter().rposition(|p| is_claims_heading(p))? + 1;
+    let end = paragraphs[start..].iter()
+        .position(|p| is_heading(p))
+        .map_or(paragraphs.len(), |offset| start + offset);
+    Some(start..end)
+}
+
+/// Earlier claims a claim refers back to, with lists and ranges like "claims 1 to 3" expanded.
+/// "Any preceding claim" refers to every claim before it.
+fn referenced_claims(text: &str, number: usize) -> Vec<usize> {
+    let reference = Regex::new(CLAIM_REFERENCE_PATTERN).unwrap();
+    let item = Regex::new(r"[0-9]+").unwrap();
+
+    let mut parents = Vec::new();
+    if Regex::new(PRECEDING_CLAIMS_PATTERN).unwrap().is_match(text) {
+        parents.extend(1..number);
+    }
+    for cap in reference.captures_iter(text) {
+        let list = cap.get(1).unwrap().as_str();
+        let mut previous: Option<usize> = None;
+        let mut previous_end = 0;
+        for m in item.find_iter(list) {
+            let Ok(claim) = m.as_str().parse::<usize>() else { continue };
+            let separator = list[previous_end..m.start()].to_lowercase();
+            let is_range = separator.contains('-') || separator.contains('–')
+                || separator.contains("to") || separator.contains("through");
+            match previous {
+                Some(start) if is_range && claim > start => parents.extend(start + 1..=claim),
+                _ => parents.push(claim),
+            }
+            previous = Some(claim);
+            previous_end = m.end();
+        }
+    }
+
+    parents.retain(|&parent| parent >= 1 && parent < number);
+    parents.sort_unstable();
+    parents.dedup();
+    parents
+}
+
+/// A word reduced to lowercase letters, e.g. "(housing," -> "housing"
+fn bare_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Skip a list of ordinals such as "first and second" or "first, second and third",
+/// returning where the name after it starts and the ordinals. A single ordinal, as in
+/// "first arm", is part of the name and is not skipped.
+fn ordinal_list(bare: &[String], start: usize) -> NameStart {
+    let is_ordinal = |i: usize| bare.get(i).is_some_and(|w| ORDINALS.contains(&w.as_str()));
+    let mut ordinals = Vec::new();
+    let mut index = start;
+    while is_ordinal(index) {
+        ordinals.push(bare[index].clone());
+        index += 1;
+        if bare.get(index).is_some_and(|w| elements::is_conjunction(w)) && is_ordinal(index + 1) {
+            index += 1;
+        }
+    }
+    if ordinals.len() < 2 {
+        return NameStart { start, ordinals: Vec::new() };
+    }
+    NameStart { start: index, ordinals }
+}
+
+/// Skip the counting words of an introduction or reference, e.g. "at least one", "two or more",
+/// "two" or "plurality of", and any list of ordinals after them
+fn skip_counting_words(bare: &[String], mut start: usize) -> NameStart {
+    let is = |offset: usize, word: &str| bare.get(start + offset).is_some_and(|w| w == word);
+    if (is(0, "at") && is(1, "least") && is(2, "one")) || ((is(0, "one") || is(0, "two")) && is(1, "or") && is(2, "more")) {
+        start += 3;
+    } else if bare.get(start).is_some_and(|w| NUMBER_WORDS.contains(&w.as_str())) {
+        start += 1;
+    }
+    if bare.get(start).is_some_and(|w| COLLECTIVE_NOUNS.contains(&w.as_str())) && bare.get(start + 1).is_some_and(|w| w == "of") {
+        start += 2;
+    }
+    ordinal_list(bare, start)
+}
+
+/// Whether the head noun of a name is plural, e.g. "springs"
+fn is_plural(name: &[&str]) -> bool {
+    name.last().is_some_and(|head| normalize_text(head) != head.to_lowercase())
+}
+
+/// Where an element is introduced: after "a", "an", "at least one", "one or more" or a
+/// number such as "two", skipping counting nouns as in "a plurality of springs"; at a list
+/// of ordinals, as in "first and second arms"; or at a plural named without an article
+/// after "comprising" or a semicolon, as in "comprising: springs; and levers"
+fn introduced_at(words: &[&str], bare: &[String], index: usize) -> Option<NameStart> {
+    let word = bare[index].as_str();
+    if word == "a" || word == "an" {
+        return Some(skip_counting_words(bare, index + 1));
+    }
+    let counted = skip_counting_words(bare, index);
+    if counted.start != index {
+        return Some(counted);
+    }
+
+    if TRANSITIONS.contains(&word) || words[index].ends_with(';') {
+        let mut start = index + 1;
+        if bare.get(start).is_some_and(|w| w == "and") {
+            start += 1;
+        }
+        if is_plural(&elements::element_name_after(words, start)) {
+            return Some(NameStart { start, ordinals: Vec::new() });
+        }
+    }
+    None
+}
+
+/// Where an element is referred back to after "the" or "said", skipping the counting
+/// words of its introduction, as in "the at least one spring", "said plurality of springs"
+/// or "the first and second arms"
+fn referenced_at(bare: &[String], index: usize) -> Option<NameStart> {
+    if bare[index] != "the" && bare[index] != "said" {
+        return None;
+    }
+    Some(skip_counting_words(bare, index + 1))
+}
+
+/// The names an introduction or reference covers: one per ordinal, as "first arm" and
+/// "second arm" for "first and second arms", or the name itself
+fn named_elements<'a>(name: &[&'a str], ordinals: &'a [String]) -> Vec<Vec<&'a str>> {
+    if ordinals.is_empty() {
+        return vec![name.to_vec()];
+    }
+    ordinals.iter()
+        .map(|ordinal| std::iter::once(ordinal.as_str()).chain(name.iter().copied()).collect())
+        .collect()
+}
+
+/// Words of a name with each one singularized by normalize_text, so "surfaces" matches "surface"
+fn singular_words(name: &[&str]) -> Vec<String> {
+    normalize_text(&name.join(" ")).split_whitespace().map(|w| w.to_string()).collect()
+}
+
+/// Find the element names a claim uses. Names numbered in the description are matched
+/// wherever they appear, longest first; elements the claim introduces with "a" or "an"
+/// that match no described name are kept with no numerals.
+fn claim_terms(text: &str, described: &[(Vec<String>, Vec<String>)]) -> Vec<ClaimTerm> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let bare: Vec<String> = words.iter().map(|w| bare_word(w)).collect();
+    let mut covered = vec![false; words.len()];
+    let mut found: Vec<(usize, ClaimTerm)> = Vec::new();
+
+    // Only the head noun is singularized, as in normalize_element_name
+    let matches_at = |index: usize, name: &[String]| {
+        name.iter().enumerate().all(|(offset, expected)| {
+            let word = &bare[index + offset];
+            let last = offset + 1 == name.len();
+            let ends_phrase = words[index + offset].ends_with(|c: char| ",;:.".contains(c));
+            if last { normalize_text(word) == *expected } else { word == expected && !ends_phrase }
+        })
+    };
+
+    for (name, numerals) in described {
+        let mut index = 0;
+        while index + name.len() <= words.len() {
+            let span = index..index + name.len();
+            if covered[span.clone()].iter().any(|&c| c) || !matches_at(index, name) {
+                index += 1;
+                continue;
+            }
+            covered[span.clone()].iter_mut().for_each(|c| *c = true);
+            let written = words[span].join(" ");
+            found.push((index, ClaimTerm {
+                name: name.join(" "),
+                text: written.trim_matches(|c: char| !c.is_alphanumeric()).to_string(),
+                numerals: numerals.clone(),
+            }));
+            index += name.len();
+        }
+    }
+
+    // Elements introduced without a description numeral
+    for index in 0..words.len() {
+        let Some(NameStart { start, ordinals }) = introduced_at(&words, &bare, index) else { continue };
+        let name = elements::element_name_after(&words, start);
+        if name.is_empty() || covered[start..start + name.len()].iter().any(|&c| c) {
+            continue;
+        }
+        for named in named_elements(&name, &ordinals) {
+            found.push((start, ClaimTerm {
+                name: elements::normalize_element_name(&named.join(" ")),
+                text: named.join(" "),
+                numerals: Vec::new(),
+            }));
+        }
+    }
+
+    found.sort_by_key(|(index, _)| *index);
+    let mut terms: Vec<ClaimTerm> = Vec::new();
+    for (_, term) in found {
+        if !terms.iter().any(|t| t.name == term.name) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Split the CLAIMS section into numbered claims with their dependencies and element names.
+/// `described` are the elements numbered outside the claims, which give each term its numerals.
+/// Paragraphs without a claim number continue the claim before them, as when each
+/// element of a claim is its own paragraph.
+pub fn parse_claims(paragraphs: &[String], described: &[SpecElement]) -> Vec<Claim> {
+    let Some(range) = claims_section(paragraphs) else {
+        println!("[DEBUG] No CLAIMS section found");
+        return Vec::new();
+    };
+
+    let claim_start = Regex::new(CLAIM_START_PATTERN).unwrap();
+    let mut claims: Vec<Claim> = Vec::new();
+    for paragraph in &paragraphs[range] {
+        let text = paragraph.trim();
+        if let Some(cap) = claim_start.captures(text) {
+            if let Ok(number) = cap[1].parse::<usize>() {
+                claims.push(Claim { number, text: cap[2].to_string(), depends_on: Vec::new(), terms: Vec::new() });
+                continue;
+            }
+        }
+        if let Some(claim) = claims.last_mut() {
+            claim.text.push(' ');
+            claim.text.push_str(text);
+        }
+    }
+
+    // Described names with their numerals, longest names first so "support arm" wins over "arm"
+    let mut numerals_by_name: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for element in described {
+        let numerals = numerals_by_name.entry(element.name.clone()).or_default();
+        if !numerals.contains(&element.numeral) {
+            numerals.push(element.numeral.clone());
+        }
+    }
+    let mut names: Vec<(Vec<String>, Vec<String>)> = numerals_by_name.into_iter()
+        .map(|(name, numerals)| (name.split_whitespace().map(|w| w.to_string()).collect(), numerals))
+        .collect();
+    names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    for claim in &mut claims {
+        claim.depends_on = referenced_claims(&claim.text, claim.number);
+        claim.terms = claim_terms(&claim.text, &names);
+    }
+
+    println!("[DEBUG] Claims found: {}, independent: {}",
+        claims.len(), claims.iter().filter(|c| c.depends_on.is_empty()).count());
+    claims
+}
+
+/// Elements every claim in `parents` provides a basis for; a claim depending on
+/// several others, as in "any of claims 1 to 3", can only rely on what they all introduce
+fn inherited_basis(parents: &[usize], basis: &BTreeMap<usize, Vec<Introduction>>) -> Vec<Introduction> {
+    let mut chains = parents.iter().filter_map(|parent| basis.get(parent));
+    let Some(first) = chains.next() else { return Vec::new() };
+    let others: Vec<&Vec<Introduction>> = chains.collect();
+    first.iter()
+        .filter(|intro| others.iter().all(|chain| chain.iter().any(|other| other.words == intro.words)))
+        .cloned()
+        .collect()
+}
+
+/// Whether a reference names all or part of an introduced element; the introduced
+/// name may run on into a verb, as in "a fastener securing ...". So may the reference,
+/// as in "the processor stores ...", so a shorter start of it ending on the introduced
+/// element's last word also has a basis.
+fn has_basis(reference: &[String], intro: &Introduction) -> bool {
+    intro.words.windows(reference.len()).any(|window| window == reference)
+        || (1..reference.len()).any(|len| intro.words.ends_with(&reference[..len]))
+}
+
+/// Check each "the X" and "said X" against the elements introduced with "a X" or "an X"
+/// earlier in the claim or in the claims it depends on. "The housing" has a basis in
+/// "a rigid housing", and "the surfaces" in "a surface". A reference without a basis that
+/// shares its head noun or its qualifiers with an introduced element, as "the support
+/// member" does with "a support arm", is reported as a renaming instead.
+pub fn antecedent_issues(claims: &[Claim]) -> Vec<AntecedentIssue> {
+    let mut basis: BTreeMap<usize, Vec<Introduction>> = BTreeMap::new();
+    let mut issues = Vec::new();
+
+    for claim in claims {
+        let mut available = inherited_basis(&claim.depends_on, &basis);
+        let words: Vec<&str> = claim.text.split_whitespace().collect();
+        let bare: Vec<String> = words.iter().map(|w| bare_word(w)).collect();
+        let mut reported: Vec<String> = Vec::new();
+
+        let mut index = 0;
+        while index < words.len() {
+            if let Some(NameStart { start, ordinals }) = referenced_at(&bare, index) {
+                let name = elements::element_name_after(&words, start);
+                let excluded = name.first().is_some_and(|w| NON_ELEMENTS.contains(&w.to_lowercase().as_str()));
+                let names = if name.is_empty() || excluded { Vec::new() } else { named_elements(&name, &ordinals) };
+                for named in names {
+                    let reference = singular_words(&named);
+                    let term = reference.join(" ");
+                    if available.iter().any(|intro| has_basis(&reference, intro)) || reported.contains(&term) {
+                        continue;
+                    }
+                    reported.push(term.clone());
+                    let text = format!("{} {}", bare[index..start].join(" "), name.join(" "));
+                    let renamed: Vec<&Introduction> = available.iter()
+                        .filter(|intro| {
+                            let same_qualifiers = reference.len() > 1 && intro.words.len() > 1
+                                && reference[..reference.len() - 1] == intro.words[..intro.words.len() - 1];
+                            same_qualifiers || elements::names_compatible(&term, &intro.words.join(" "))
+                        })
+                        .collect();
+                    if renamed.is_empty() {
+                        issues.push(AntecedentIssue::MissingAntecedent { claim: claim.number, term, text });
+                    } else {
+                        let mut introduced_as: Vec<String> = Vec::new();
+                        for intro in &renamed {
+                            if !introduced_as.contains(&intro.text) {
+                                introduced_as.push(intro.text.clone());
+                            }
+                        }
+                        let mut introduced_in: Vec<usize> = renamed.iter().map(|intro| intro.claim).collect();
+                        introduced_in.sort_unstable();
+                        introduced_in.dedup();
+                        issues.push(AntecedentIssue::InconsistentName { claim: claim.number, term, text, introduced_as, introduced_in });
+                    }
+                }
+                index = start;
+                continue;
+            }
+
+            if let Some(NameStart { start, ordinals }) = introduced_at(&words, &bare, index) {
+                let name = elements::element_name_after(&words, start);
+                if !name.is_empty() {
+                    for named in named_elements(&name, &ordinals) {
+                        available.push(Introduction { words: singular_words(&named), text: named.join(" "), claim: claim.number });
+                    }
+                    if !ordinals.is_empty() {
+                        available.push(Introduction { words: singular_words(&name), text: name.join(" "), claim: claim.number });
+                    }
+                }
+                index = start;
+                continue;
+            }
+            index += 1;
+        }
+
+        basis.insert(claim.number, available);
+    }
+
+    println!("[DEBUG] Antecedent basis issues found: {}", issues.len());
+    issues
+}
+
+/// Group the terms that satisfy `keep` by name, listing every claim that uses each
+pub fn claim_term_references(claims: &[Claim], keep: impl Fn(&ClaimTerm) -> bool) -> Vec<ClaimTermReference> {
+    let mut references: Vec<ClaimTermReference> = Vec::new();
+    for claim in claims {
+        for term in claim.terms.iter().filter(|t| keep(t)) {
+            let index = match references.iter().position(|r| r.name == term.name) {
+                Some(index) => index,
+                None => {
+                    references.push(ClaimTermReference {
+                        name: term.name.clone(),
+                        claims: Vec::new(),
+                        texts: Vec::new(),
+                        numerals: term.numerals.clone(),
+                    });
+                    references.len() - 1
+                }
+            };
+            let reference = &mut references[index];
+            if !reference.claims.contains(&claim.number) {
+                reference.claims.push(claim.number);
+            }
+            if !reference.texts.contains(&term.text) {
+                reference.texts.push(term.text.clone());
+            }
+        }
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(texts: &[&str]) -> Vec<Claim> {
+        let mut paragraphs = vec!["CLAIMS".to_string()];
+        paragraphs.extend(texts.iter().map(|text| text.to_string()));
+        parse_claims(&paragraphs, &[])
+    }
+
+    fn missing(texts: &[&str]) -> Vec<(usize, String)> {
+        antecedent_issues(&claims(texts)).into_iter()
+            .filter_map(|issue| match issue {
+                AntecedentIssue::MissingAntecedent { claim, term, .. } => Some((claim, term)),
+                AntecedentIssue::InconsistentName { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn claims_headings() {
+        assert!(is_claims_heading("CLAIMS"));
+        assert!(is_claims_heading("What is claimed is:"));
+        assert!(is_claims_heading("We claim."));
+        assert!(!is_claims_heading("The claims below"));
+    }
+
+    #[test]
+    fn claims_section_ends_at_the_next_heading() {
+        let paragraphs: Vec<String> = ["DETAILED DESCRIPTION", "A housing 102.", "What is claimed is:", "1. A device.", "2. The device of claim 1.", "ABSTRACT", "A device."]
+            .iter().map(|p| p.to_string()).collect();
+        assert_eq!(claims_section(&paragraphs), Some(3..5));
+        assert_eq!(claims_section(&paragraphs[..2]), None);
+    }
+
+    #[test]
+    fn claims_are_split_at_their_numbers() {
+        let claims = claims(&[
+            "1. A device comprising:",
+            "a housing; and",
+            "a lid.",
+            "Claim 2) The device of claim 1, wherein the lid is hinged.",
+        ]);
+        let numbers: Vec<usize> = claims.iter().map(|claim| claim.number).collect();
+        assert_eq!(numbers, vec![1, 2]);
+        assert_eq!(claims[0].text, "A device comprising: a housing; and a lid.");
+        assert!(claims[0].depends_on.is_empty());
+        assert_eq!(claims[1].text, "The device of claim 1, wherein the lid is hinged.");
+        assert_eq!(claims[1].depends_on, vec![1]);
+    }
+
+    #[test]
+    fn referenced_claim_lists_and_ranges() {
+        assert_eq!(referenced_claims("The device of claim 3", 5), vec![3]);
+        assert_eq!(referenced_claims("The device of claims 1 or 2", 5), vec![1, 2]);
+        assert_eq!(referenced_claims("The device of any of claims 1 to 4", 5), vec![1, 2, 3, 4]);
+        assert_eq!(referenced_claims("The device of any of claims 2-4", 6), vec![2, 3, 4]);
+        assert_eq!(referenced_claims("The device of claim 1, 2 through 3 or claim 5", 6), vec![1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn references_to_later_claims_are_dropped() {
+        assert_eq!(referenced_claims("The device of claim 7", 5), Vec::<usize>::new());
+        assert_eq!(referenced_claims("The device of any of claims 1 to 9", 4), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn claim_terms_take_described_numerals() {
+        let described = [("support arm", "104"), ("arm", "106")].map(|(name, numeral)| SpecElement {
+            name: name.to_string(),
+            numeral: numeral.to_string(),
+            text: format!("{} {}", name, numeral),
+            expanded_from: None,
+            range_interior: false,
+            figures: Vec::new(),
+            origin: crate::ParagraphOrigin::Body,
+        });
+        let paragraphs = ["CLAIMS", "1. A device comprising support arms and an arm coupled to a latch."].map(String::from);
+        let claims = parse_claims(&paragraphs, &described);
+        let terms: Vec<(&str, &str, Vec<String>)> = claims[0].terms.iter()
+            .map(|term| (term.name.as_str(), term.text.as_str(), term.numerals.clone()))
+            .collect();
+        assert_eq!(terms, vec![
+            ("device", "device", vec![]),
+            ("support arm", "support arms", vec!["104".to_string()]),
+            ("arm", "arm", vec!["106".to_string()]),
+            ("latch", "latch", vec![]),
+        ]);
+    }
+
+    #[test]
+    fn preceding_claims_are_all_earlier_claims() {
+        assert_eq!(referenced_claims("The device of any preceding claim, wherein", 4), vec![1, 2, 3]);
+        assert_eq!(referenced_claims("The device according to any one of the previous claims", 3), vec![1, 2]);
+        assert_eq!(referenced_claims("The device of any preceding claim", 1), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn preceding_claims_inherit_what_all_of_them_introduce() {
+        let issues = missing(&[
+            "1. A device comprising a housing.",
+            "2. The device of claim 1, further comprising a latch.",
+            "3. The device of any preceding claim, wherein the housing is rigid and the latch is closed.",
+        ]);
+        assert_eq!(issues, vec![(3, "latch".to_string())]);
+    }
+
+    #[test]
+    fn ordinal_lists_introduce_each_element() {
+        let issues = missing(&["1. A device comprising first and second arms, the first arm coupled to the second arm, and the arms coupled to the lever."]);
+        assert_eq!(issues, vec![(1, "lever".to_string())]);
+        let issues = antecedent_issues(&claims(&["1. A device comprising a first, second and third plate, wherein the third plate and the fourth plate are flat."]));
+        let terms: Vec<&str> = issues.iter()
+            .map(|issue| match issue {
+                AntecedentIssue::MissingAntecedent { term, .. } | AntecedentIssue::InconsistentName { term, .. } => term.as_str(),
+            })
+            .collect();
+        assert_eq!(terms, vec!["fourth plate"]);
+    }
+
+    #[test]
+    fn references_to_ordinal_lists_need_each_element() {
+        let issues = missing(&["1. A device comprising a first arm, wherein the first and second arms are parallel."]);
+        assert_eq!(issues, vec![(1, "second arm".to_string())]);
+    }
+
+    #[test]
+    fn numbers_and_bare_plurals_introduce_elements() {
+        assert!(missing(&["1. A device comprising two arms, the arms being parallel."]).is_empty());
+        assert!(missing(&["1. A device comprising two or more arms, wherein the arms are parallel."]).is_empty());
+        assert!(missing(&["1. A device comprising: springs; and levers, the springs biasing the levers."]).is_empty());
+        assert!(missing(&["1. A device comprising a housing having flat surfaces, wherein the surfaces are polished."]).is_empty());
+    }
+
+    #[test]
+    fn singular_nouns_without_an_article_introduce_nothing() {
+        let issues = missing(&["1. A device comprising: housing; and the lever."]);
+        assert_eq!(issues, vec![(1, "lever".to_string())]);
+    }
+
+    #[test]
+    fn references_may_run_on_into_a_verb() {
+        assert!(missing(&["1. A device comprising a processor configured to receive a signal, wherein the processor stores the signal."]).is_empty());
+        assert!(missing(&["1. A device comprising a rigid housing, wherein the housing encloses a lever."]).is_empty());
+        let issues = missing(&["1. A device comprising a processor, wherein the memory stores it."]);
+        assert_eq!(issues, vec![(1, "memory store it".to_string())]);
+    }
+
+    #[test]
+    fn ordinal_lists_are_claim_terms() {
+        let claims = claims(&["1. A device comprising first and second arms."]);
+        let names: Vec<&str> = claims[0].terms.iter().map(|term| term.name.as_str()).collect();
+        assert_eq!(names, vec!["device", "first arm", "second arm"]);
+    }
+}
@@ -0,0 +1,666 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use image::RgbImage;
+
+use crate::claims::{self, ClaimTermReference};
+use crate::figures::FigureDescription;
+use crate::fuzzy;
+use crate::layout::{self, FigureRegion};
+use crate::orientation::LabelMatcher;
+use crate::{elements, normalize_number, DocxResult, LabelGrammar, OcrResult, ParagraphOrigin, SpecElement, LOW_CONFIDENCE};
+
+/// Where a numeral was seen in the drawings
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct Occurrence {
+    pub page: usize,     // 1-based page number
+    pub bbox: [f32; 4],  // Normalized [x1, y1, x2, y2] of the OCR result
+    pub figure: Option<String>,  // Figure the result was clustered into, if the sheet has FIG. labels
+    pub confidence: f32,         // How sure detection was that the box holds text, from 0 to 1; see orientation::word_confidence
+    pub label_fit: f32,          // How well the OCR result fits a label, from 0 to 1; see orientation::label_fit
+}
+
+/// A numeral found in the drawings, with every place it was seen
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DrawingNumeral {
+    pub numeral: String,
+    pub figures: Vec<String>,  // Every figure the numeral appears in, e.g. ["FIG. 3", "FIG. 5"]
+    pub occurrences: Vec<Occurrence>,
+}
+
+/// A numeral found in the specification, with the phrases that introduced it
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SpecNumeral {
+    pub numeral: String,
+    pub references: Vec<String>,     // Full matches like "housing 102"
+    pub expanded_from: Vec<String>,  // Lists or ranges the numeral was expanded from, e.g. "legs 110a-110c"
+    pub origins: Vec<ParagraphOrigin>,  // Parts of the document that introduce it, e.g. ["body", "table_cell"]; empty for FIG. labels
+}
+
+/// A numeral present on both sides
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct NumeralMatch {
+    pub numeral: String,
+    pub references: Vec<String>,
+    pub expanded_from: Vec<String>,
+    pub origins: Vec<ParagraphOrigin>,
+    pub figures: Vec<String>,
+    pub occurrences: Vec<Occurrence>,
+}
+
+/// A numeral the spec discusses in one figure but OCR only found in others
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FigureMismatch {
+    pub numeral: String,
+    pub stated_figures: Vec<String>,  // Figures the text puts the numeral in, e.g. ["FIG. 4"]
+    pub found_in: Vec<String>,        // Figures OCR actually found it in
+    pub pages: Vec<usize>,            // Sheets OCR found it on
+    pub references: Vec<String>,      // Phrases that introduced it in that figure context
+}
+
+/// A drawing finding that is more likely an OCR misread than a real discrepancy
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReviewItem {
+    /// A drawing reading one character away from a spec numeral, e.g. OCR "1O8" for "108"
+    ProbableMisread { read_as: String, numeral: String, references: Vec<String>, occurrences: Vec<Occurrence> },
+    /// A numeral missing from the spec that OCR only ever read with low confidence
+    LowConfidence { numeral: String, figures: Vec<String>, occurrences: Vec<Occurrence> },
+}
+
+impl ReviewItem {
+    /// The numeral the item is about, as the spec or the drawings have it
+    fn numeral(&self) -> &str {
+        match self {
+            ReviewItem::ProbableMisread { numeral, .. } | ReviewItem::LowConfidence { numeral, .. } => numeral,
+        }
+    }
+}
+
+/// A drawing reading that fits no spec numeral as read, with the numerals it most likely stands for.
+/// Only a suggestion: the reading still counts as found in the drawings as it was read.
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct Suggestion {
+    pub read_as: String,         // The reading as OCR gave it, e.g. "1160-1"
+    pub numerals: Vec<String>,   // Spec numerals it most likely stands for, e.g. ["160-1"]
+    pub score: f32,              // 1 for an exact reading, lower the more edits were needed
+    pub occurrences: Vec<Occurrence>,
+}
+
+/// An inconsistency between element names and numerals in the specification
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NamingConflict {
+    /// One numeral introduced under several names, e.g. "housing 102" and "frame 102"
+    NumeralWithSeveralNames { numeral: String, names: Vec<String>, references: Vec<String> },
+    /// One name given several numerals, e.g. "housing 102" and "housing 202"
+    NameWithSeveralNumerals { name: String, numerals: Vec<String>, references: Vec<String> },
+}
+
+#[derive(serde::Serialize, Debug, Default)]
+pub struct CrossCheckReport {
+    pub drawing_only: Vec<DrawingNumeral>,                // In the drawings but not in the spec
+    pub spec_only: Vec<SpecNumeral>,                      // In the spec but not in any drawing
+    pub range_numerals_missing_from_drawings: Vec<SpecNumeral>,  // Only inside a spec range like "walls 120-124" and not in any drawing; even numbering may skip them
+    pub figures_missing_from_drawings: Vec<SpecNumeral>,  // FIG. labels the spec mentions but OCR never found
+    pub figures_missing_from_spec: Vec<DrawingNumeral>,   // FIG. labels on the sheets the spec never mentions
+    pub matches: Vec<NumeralMatch>,                       // Numerals and FIG. labels found on both sides
+    pub naming_conflicts: Vec<NamingConflict>,            // Numerals or names used inconsistently in the spec
+    pub declared_figures_missing_from_drawings: Vec<FigureDescription>,  // In the figure list but never found by OCR
+    pub undeclared_figures_in_drawings: Vec<DrawingNumeral>,             // On the sheets but not in the figure list
+    pub declared_figures_not_referenced: Vec<FigureDescription>,         // In the figure list but never discussed in the detailed description
+    pub figure_regions: Vec<FigureRegion>,                               // Area each figure covers on its sheet
+    pub numerals_missing_from_stated_figure: Vec<FigureMismatch>,        // Discussed "with reference to FIG. N" but not found in it
+    pub needs_review: Vec<ReviewItem>,                                   // Probable misreads, kept out of drawing_only and spec_only
+    pub suggestions: Vec<Suggestion>,                                    // Likely intended spec numerals for unmatched readings
+    pub claim_terms_missing_from_drawings: Vec<ClaimTermReference>,      // Claimed elements whose description numerals OCR never found
+}
+
+/// Whether a figure found on a sheet is the stated one or one of its views, e.g. FIG. 1 covers FIG. 1A
+fn figure_covers(stated: &str, found: &str) -> bool {
+    found == stated
+        || found.strip_prefix(stated).is_some_and(|rest| rest.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Find numerals the spec places in a figure that OCR only found in other figures.
+/// Figures that never made it onto a sheet are skipped, since there is nothing to compare.
+fn figure_mismatches(drawing: &BTreeMap<String, DrawingNumeral>, elements: &[SpecElement], regions: &[FigureRegion]) -> Vec<FigureMismatch> {
+    let mut mismatches: BTreeMap<(String, Vec<String>), FigureMismatch> = BTreeMap::new();
+    for element in elements.iter().filter(|e| !e.figures.is_empty()) {
+        let Some(found) = drawing.get(&comparison_key(&element.numeral)) else { continue };
+        if found.figures.is_empty() {
+            continue;
+        }
+
+        let on_sheets = element.figures.iter()
+            .any(|stated| regions.iter().any(|region| figure_covers(stated, &region.figure)));
+        let in_stated = element.figures.iter()
+            .any(|stated| found.figures.iter().any(|figure| figure_covers(stated, figure)));
+        if !on_sheets || in_stated {
+            continue;
+        }
+
+        let mismatch = mismatches.entry((element.numeral.to_lowercase(), element.figures.clone()))
+            .or_insert_with(|| {
+                let mut pages: Vec<usize> = found.occurrences.iter().map(|o| o.page).collect();
+                pages.dedup();
+                FigureMismatch {
+                    numeral: element.numeral.clone(),
+                    stated_figures: element.figures.clone(),
+                    found_in: found.figures.clone(),
+                    pages,
+                    references: Vec::new(),
+                }
+            });
+        if !mismatch.references.contains(&element.text) {
+            mismatch.references.push(element.text.clone());
+        }
+    }
+
+    let mut mismatches: Vec<FigureMismatch> = mismatches.into_values().collect();
+    mismatches.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    mismatches
+}
+
+/// Canonical form of a FIG. reference, e.g. "fig 1b" -> "FIG. 1B"
+pub fn canonical_figure(number: &str, letter: Option<&str>) -> String {
+    format!("FIG. {}{}", number, letter.unwrap_or("").to_uppercase())
+}
+
+/// Key used to decide whether two labels refer to the same numeral
+fn comparison_key(label: &str) -> String {
+    if label.starts_with("FIG.") {
+        label.to_string()
+    } else {
+        label.to_lowercase()
+    }
+}
+
+/// Split a piece of text into its FIG. labels and reference numerals
+fn labels_in(text: &str, matcher: &LabelMatcher) -> Vec<String> {
+    let fig_regex = &matcher.fig_regex;
+    let mut labels = Vec::new();
+
+    // FIG. labels first, then whatever numerals remain once they are removed
+    for cap in fig_regex.captures_iter(text) {
+        let number = cap.get(2).unwrap().as_str();
+        labels.push(canonical_figure(number, cap.get(3).map(|m| m.as_str())));
+    }
+    let remainder = fig_regex.replace_all(text, " ");
+
+    let normalized = normalize_number(&remainder, matcher);
+    labels.extend(normalized.split_whitespace().map(|s| s.to_string()));
+    labels
+}
+
+/// Order numerals by their leading number, falling back to plain string order
+fn numeral_order(a: &str, b: &str) -> Ordering {
+    let digits = |s: &str| {
+        s.trim_start_matches("FIG. ")
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse::<i32>()
+    };
+    match (digits(a), digits(b)) {
+        (Ok(a_val), Ok(b_val)) => a_val.cmp(&b_val).then_with(|| a.cmp(b)),
+        _ => a.cmp(b),
+    }
+}
+
+/// Leading digits of a numeral, so "110a" and "110b" count as one family
+fn numeral_base(numeral: &str) -> &str {
+    let end = numeral.find(|c: char| !c.is_ascii_digit()).unwrap_or(numeral.len());
+    &numeral[..end]
+}
+
+/// The characters two labels differ in, when they differ by exactly one substituted, inserted
+/// or dropped character, as (character in the shorter label, character in the longer label).
+/// A letter suffix alone ("108" and "108a") marks a deliberate variant, not a misread.
+fn single_difference(a: &str, b: &str) -> Option<(Option<char>, char)> {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let (shorter, longer) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    if longer.len() - shorter.len() > 1 {
+        return None;
+    }
+
+    // Skip the common prefix and suffix; at most one character may remain on either side
+    let prefix = shorter.iter().zip(longer.iter()).take_while(|(x, y)| x == y).count();
+    let suffix = shorter[prefix..].iter().rev().zip(longer[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (rest_shorter, rest_longer) = (shorter.len() - prefix - suffix, longer.len() - prefix - suffix);
+    if rest_shorter > 1 || rest_longer != 1 {
+        return None;
+    }
+
+    let is_letter_suffix = rest_shorter == 0 && suffix == 0 && longer[prefix].is_alphabetic()
+        && shorter.last().is_some_and(|c| c.is_ascii_digit());
+    if is_letter_suffix {
+        return None;
+    }
+    Some(((rest_shorter == 1).then(|| shorter[prefix]), longer[prefix]))
+}
+
+/// Whether a drawing reading is likely a misread of a spec numeral. The two must differ by a
+/// single character, and that difference must be one OCR readily makes, such as "O" for "0",
+/// unless OCR read it with so little confidence that any single-character slip is plausible.
+fn is_probable_misread(read_as: &str, numeral: &str, uncertain: bool) -> bool {
+    match single_difference(read_as, numeral) {
+        Some((Some(a), b)) => fuzzy::confusable(a, b) || uncertain,
+        Some((None, _)) => uncertain,
+        None => false,
+    }
+}
+
+/// The spec numeral a drawing reading is most likely a misread of, preferring numerals
+/// the drawings are missing, since the misread is the likeliest reason they are missing
+fn probable_numeral<'a>(read_as: &str, uncertain: bool, spec: &'a BTreeMap<String, SpecNumeral>, drawing: &BTreeMap<String, DrawingNumeral>) -> Option<&'a SpecNumeral> {
+    let mut candidates: Vec<&SpecNumeral> = spec.iter()
+        .filter(|(key, _)| !key.starts_with("FIG.") && is_probable_misread(read_as, key, uncertain))
+        .map(|(_, numeral)| numeral)
+        .collect();
+    candidates.sort_by(|a, b| {
+        let found = |n: &SpecNumeral| drawing.contains_key(&comparison_key(&n.numeral));
+        found(a).cmp(&found(b)).then_with(|| numeral_order(&a.numeral, &b.numeral))
+    });
+    candidates.into_iter().next()
+}
+
+/// Find numerals given several names and names given several numerals
+pub fn naming_conflicts(elements: &[SpecElement]) -> Vec<NamingConflict> {
+    let mut names_by_numeral: BTreeMap<String, Vec<&SpecElement>> = BTreeMap::new();
+    let mut numerals_by_name: BTreeMap<String, Vec<&SpecElement>> = BTreeMap::new();
+    for element in elements {
+        names_by_numeral.entry(element.numeral.to_lowercase()).or_default().push(element);
+        numerals_by_name.entry(element.name.clone()).or_default().push(element);
+    }
+
+    let references = |group: &[&SpecElement]| {
+        let mut texts: Vec<String> = Vec::new();
+        for element in group {
+            if !texts.contains(&element.text) {
+                texts.push(element.text.clone());
+            }
+        }
+        texts
+    };
+
+    let mut conflicts = Vec::new();
+
+    for group in names_by_numeral.values() {
+        // Shorter and longer forms of one name ("web", "inflatable web") are not a conflict
+        let names: BTreeSet<&str> = group.iter().map(|e| e.name.as_str()).collect();
+        let conflicting = names.iter()
+            .any(|a| names.iter().any(|b| !elements::names_compatible(a, b)));
+        if conflicting {
+            conflicts.push(NamingConflict::NumeralWithSeveralNames {
+                numeral: group[0].numeral.clone(),
+                names: names.into_iter().map(|s| s.to_string()).collect(),
+                references: references(group),
+            });
+        }
+    }
+
+    for (name, group) in &numerals_by_name {
+        // Lettered or hyphenated variants of one numeral (110a, 110b) are intentional, and so
+        // are numerals listed together as in "inlets 128 and 146"; merge those into families
+        let mut families: Vec<BTreeSet<&str>> = Vec::new();
+        for element in group.iter() {
+            let mut family: BTreeSet<&str> = BTreeSet::from([numeral_base(&element.numeral)]);
+            if let Some(source) = &element.expanded_from {
+                family.extend(group.iter()
+                    .filter(|other| other.expanded_from.as_ref() == Some(source))
+                    .map(|other| numeral_base(&other.numeral)));
+            }
+            let (overlapping, mut rest): (Vec<_>, Vec<_>) = families.into_iter()
+                .partition(|existing| !existing.is_disjoint(&family));
+            for existing in overlapping {
+                family.extend(existing);
+            }
+            rest.push(family);
+            families = rest;
+        }
+        if families.len() > 1 {
+            let mut numerals: Vec<String> = group.iter().map(|e| e.numeral.clone()).collect();
+            numerals.sort_by(|a, b| numeral_order(a, b));
+            numerals.dedup();
+            conflicts.push(NamingConflict::NameWithSeveralNumerals {
+                name: name.clone(),
+                numerals,
+                references: references(group),
+            });
+        }
+    }
+
+    println!("[DEBUG] Naming conflicts found: {}", conflicts.len());
+    conflicts
+}
+
+/// Compare the numerals OCR'd from the drawings against those extracted from the specification
+pub fn cross_check(pages: &[(usize, RgbImage, Vec<OcrResult>)], docx: &DocxResult, grammar: &LabelGrammar) -> Result<CrossCheckReport> {
+    let matcher = LabelMatcher::new(grammar)?;
+    let fig_regex = &matcher.fig_regex;
+
+    // Collect every drawing label with the page, box and figure it came from
+    let mut drawing: BTreeMap<String, DrawingNumeral> = BTreeMap::new();
+    let mut unparsed: BTreeMap<String, (String, Vec<Occurrence>)> = BTreeMap::new();
+    let mut readings: BTreeMap<String, (String, Vec<String>, Vec<Occurrence>)> = BTreeMap::new();
+    let mut figure_regions = Vec::new();
+    for (page, _, ocr_results) in pages {
+        let figures = layout::assign_figures(ocr_results);
+        figure_regions.extend(layout::figure_regions(*page, ocr_results, &figures));
+
+        for (result, figure) in ocr_results.iter().zip(figures) {
+            let labels = labels_in(&result.text, &matcher);
+
+            // Keep every reading with digits so unmatched ones can be fuzzy-matched against the spec
+            let cleaned = grammar.clean_token(&result.text);
+            let occurrence = Occurrence { page: *page, bbox: result.bbox, figure: figure.clone(), confidence: result.confidence, label_fit: result.label_fit };
+            if !fig_regex.is_match(&result.text) && cleaned.chars().count() >= 2 && cleaned.chars().any(|c| c.is_ascii_digit()) {
+                readings.entry(cleaned.to_lowercase())
+                    .or_insert_with(|| (cleaned.clone(), labels.clone(), Vec::new()))
+                    .2
+                    .push(occurrence.clone());
+
+                // Readings that fit no label may be numerals with a misread character
+                if labels.is_empty() {
+                    unparsed.entry(cleaned.to_lowercase())
+                        .or_insert_with(|| (cleaned.clone(), Vec::new()))
+                        .1
+                        .push(occurrence.clone());
+                }
+            }
+
+            for label in labels {
+                let entry = drawing.entry(comparison_key(&label))
+                    .or_insert_with(|| DrawingNumeral { numeral: label, figures: Vec::new(), occurrences: Vec::new() });
+                if let Some(figure) = &figure {
+                    if !entry.figures.contains(figure) {
+                        entry.figures.push(figure.clone());
+                    }
+                }
+                entry.occurrences.push(occurrence.clone());
+            }
+        }
+    }
+    for numeral in drawing.values_mut() {
+        numeral.figures.sort_by(|a, b| numeral_order(a, b));
+    }
+
+    // Collect the spec's FIG. labels, then every numeral with the phrases that introduced it
+    let mut spec: BTreeMap<String, SpecNumeral> = BTreeMap::new();
+    for number in docx.numbers.iter().filter(|n| n.starts_with("FIG.")) {
+        for label in labels_in(number, &matcher) {
+            spec.entry(comparison_key(&label))
+                .or_insert_with(|| SpecNumeral { numeral: label.clone(), references: vec![label], expanded_from: Vec::new(), origins: Vec::new() });
+        }
+    }
+    let mut stated: BTreeSet<String> = BTreeSet::new();
+    for element in &docx.elements {
+        if !element.range_interior {
+            stated.insert(comparison_key(&element.numeral));
+        }
+        let entry = spec.entry(comparison_key(&element.numeral))
+            .or_insert_with(|| SpecNumeral { numeral: element.numeral.clone(), references: Vec::new(), expanded_from: Vec::new(), origins: Vec::new() });
+        if !entry.origins.contains(&element.origin) {
+            entry.origins.push(element.origin);
+        }
+        if !entry.references.contains(&element.text) {
+            entry.references.push(element.text.clone());
+        }
+        if let Some(source) = &element.expanded_from {
+            let source = format!("{} {}", element.text.rsplit_once(' ').map_or("", |(name, _)| name), source);
+            if !entry.expanded_from.contains(&source) {
+                entry.expanded_from.push(source);
+            }
+        }
+    }
+
+    let mut report = CrossCheckReport { figure_regions, ..Default::default() };
+
+    for (key, drawing_numeral) in &drawing {
+        match spec.get(key) {
+            Some(spec_numeral) => report.matches.push(NumeralMatch {
+                numeral: spec_numeral.numeral.clone(),
+                references: spec_numeral.references.clone(),
+                expanded_from: spec_numeral.expanded_from.clone(),
+                origins: spec_numeral.origins.clone(),
+                figures: drawing_numeral.figures.clone(),
+                occurrences: drawing_numeral.occurrences.clone(),
+            }),
+            None if key.starts_with("FIG.") => report.figures_missing_from_spec.push(drawing_numeral.clone()),
+            None => report.drawing_only.push(drawing_numeral.clone()),
+        }
+    }
+
+    for (key, spec_numeral) in &spec {
+        if drawing.contains_key(key) {
+            continue;
+        }
+        if key.starts_with("FIG.") {
+            report.figures_missing_from_drawings.push(spec_numeral.clone());
+        } else if !stated.contains(key) {
+            report.range_numerals_missing_from_drawings.push(spec_numeral.clone());
+        } else {
+            report.spec_only.push(spec_numeral.clone());
+        }
+    }
+
+    // Suggest the spec numerals behind readings that do not match the spec as read
+    let spec_numerals: Vec<String> = spec.iter()
+        .filter(|(key, _)| !key.starts_with("FIG."))
+        .map(|(_, numeral)| numeral.numeral.clone())
+        .collect();
+    for (read_as, labels, occurrences) in readings.into_values() {
+        if !labels.is_empty() && labels.iter().all(|label| spec.contains_key(&comparison_key(label))) {
+            continue;
+        }
+        let Some(fuzzy_match) = fuzzy::best_match(&read_as, &spec_numerals) else { continue };
+        if fuzzy_match.numerals.iter().map(|n| comparison_key(n)).eq(labels.iter().map(|l| comparison_key(l))) {
+            continue;
+        }
+        report.suggestions.push(Suggestion {
+            read_as,
+            numerals: fuzzy_match.numerals,
+            score: fuzzy_match.score,
+            occurrences,
+        });
+    }
+    report.suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| numeral_order(&a.read_as, &b.read_as)));
+
+    // Move probable misreads out of the hard mismatches into the review queue
+    let mut misread_numerals: BTreeSet<String> = BTreeSet::new();
+    let uncertain = |occurrences: &[Occurrence]| occurrences.iter().all(|o| o.confidence < LOW_CONFIDENCE);
+    for (read_as, occurrences) in unparsed.into_values() {
+        if let Some(spec_numeral) = probable_numeral(&read_as, uncertain(&occurrences), &spec, &drawing) {
+            misread_numerals.insert(comparison_key(&spec_numeral.numeral));
+            report.needs_review.push(ReviewItem::ProbableMisread {
+                read_as,
+                numeral: spec_numeral.numeral.clone(),
+                references: spec_numeral.references.clone(),
+                occurrences,
+            });
+        }
+    }
+    let mut certain_drawing_only = Vec::new();
+    for drawing_numeral in std::mem::take(&mut report.drawing_only) {
+        let missing_from_drawings = |key: &String| !drawing.contains_key(key);
+        let read_uncertainly = uncertain(&drawing_numeral.occurrences);
+        let misread_of = spec.iter()
+            .filter(|(key, _)| !key.starts_with("FIG.") && missing_from_drawings(key))
+            .find(|(key, _)| is_probable_misread(&drawing_numeral.numeral, key, read_uncertainly));
+        if let Some((key, spec_numeral)) = misread_of {
+            misread_numerals.insert(key.clone());
+            report.needs_review.push(ReviewItem::ProbableMisread {
+                read_as: drawing_numeral.numeral,
+                numeral: spec_numeral.numeral.clone(),
+                references: spec_numeral.references.clone(),
+                occurrences: drawing_numeral.occurrences,
+            });
+        } else if read_uncertainly {
+            report.needs_review.push(ReviewItem::LowConfidence {
+                numeral: drawing_numeral.numeral,
+                figures: drawing_numeral.figures,
+                occurrences: drawing_numeral.occurrences,
+            });
+        } else {
+            certain_drawing_only.push(drawing_numeral);
+        }
+    }
+    report.drawing_only = certain_drawing_only;
+    report.spec_only.retain(|spec_numeral| !misread_numerals.contains(&comparison_key(&spec_numeral.numeral)));
+    report.range_numerals_missing_from_drawings.retain(|spec_numeral| !misread_numerals.contains(&comparison_key(&spec_numeral.numeral)));
+
+    report.drawing_only.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    report.spec_only.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    report.range_numerals_missing_from_drawings.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    report.figures_missing_from_drawings.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    report.figures_missing_from_spec.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    report.matches.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    report.needs_review.sort_by(|a, b| numeral_order(a.numeral(), b.numeral()));
+
+    report.naming_conflicts = naming_conflicts(&docx.elements);
+    report.numerals_missing_from_stated_figure = figure_mismatches(&drawing, &docx.elements, &report.figure_regions);
+
+    // Check the formal figure list against the sheets and the detailed description
+    if !docx.figure_descriptions.is_empty() {
+        for declared in &docx.figure_descriptions {
+            if !drawing.contains_key(&declared.figure) {
+                report.declared_figures_missing_from_drawings.push(declared.clone());
+            }
+            if !docx.detailed_description_figures.contains(&declared.figure) {
+                report.declared_figures_not_referenced.push(declared.clone());
+            }
+        }
+        report.undeclared_figures_in_drawings = drawing.iter()
+            .filter(|(key, _)| key.starts_with("FIG.") && !docx.figure_descriptions.iter().any(|d| &d.figure == *key))
+            .map(|(_, numeral)| numeral.clone())
+            .collect();
+        report.undeclared_figures_in_drawings.sort_by(|a, b| numeral_order(&a.numeral, &b.numeral));
+    }
+
+    // Every claimed element with a description numeral should be shown in the drawings
+    report.claim_terms_missing_from_drawings = claims::claim_term_references(&docx.claims, |term| {
+        !term.numerals.is_empty() && !term.numerals.iter().any(|numeral| drawing.contains_key(&comparison_key(numeral)))
+    });
+
+    println!("[DEBUG] Cross-check: {} matched, {} drawing-only, {} spec-only, {} to review, {} suggestions",
+        report.matches.len(), report.drawing_only.len(), report.spec_only.len(), report.needs_review.len(), report.suggestions.len());
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResultSource;
+
+    fn reading(text: &str, x: f32) -> OcrResult {
+        OcrResult {
+            text: text.to_string(),
+            bbox: [x, 0.1, x + 0.02, 0.12],
+            quad: [[x, 0.1], [x + 0.02, 0.1], [x + 0.02, 0.12], [x, 0.12]],
+            line: 0,
+            line_text: text.to_string(),
+            confidence: 0.9,
+            label_fit: 0.95,
+            source: ResultSource::Ocr,
+        }
+    }
+
+    fn element(name: &str, numeral: &str, expanded_from: Option<&str>, range_interior: bool) -> SpecElement {
+        SpecElement {
+            name: name.to_string(),
+            numeral: numeral.to_string(),
+            text: format!("{} {}", name, numeral),
+            expanded_from: expanded_from.map(String::from),
+            range_interior,
+            figures: Vec::new(),
+            origin: ParagraphOrigin::Body,
+        }
+    }
+
+    fn spec(elements: Vec<SpecElement>) -> DocxResult {
+        DocxResult {
+            full_matches: Vec::new(),
+            numbers: Vec::new(),
+            paragraphs: Vec::new(),
+            origins: Vec::new(),
+            elements,
+            figure_descriptions: Vec::new(),
+            detailed_description_figures: Vec::new(),
+            claims: Vec::new(),
+            unsupported_claim_terms: Vec::new(),
+            antecedent_issues: Vec::new(),
+        }
+    }
+
+    fn numerals<T>(items: &[T], numeral: impl Fn(&T) -> &str) -> Vec<&str> {
+        items.iter().map(numeral).collect()
+    }
+
+    #[test]
+    fn range_interiors_missing_from_the_drawings_are_noted_apart() {
+        let range = |numeral: &str, interior: bool| element("wall", numeral, Some("120-124"), interior);
+        let docx = spec(vec![
+            range("120", false), range("121", true), range("122", true), range("123", true), range("124", false),
+            element("rib", "123", None, false),
+        ]);
+        let pages = vec![(1, RgbImage::new(1, 1), vec![reading("120", 0.1), reading("122", 0.3)])];
+        let report = cross_check(&pages, &docx, &LabelGrammar::default()).unwrap();
+        assert_eq!(numerals(&report.spec_only, |n| &n.numeral), vec!["123", "124"]);
+        assert_eq!(numerals(&report.range_numerals_missing_from_drawings, |n| &n.numeral), vec!["121"]);
+        assert_eq!(numerals(&report.matches, |n| &n.numeral), vec!["120", "122"]);
+    }
+
+    #[test]
+    fn readings_are_routed_by_how_well_they_fit_the_spec() {
+        let docx = spec(vec![
+            element("housing", "102", None, false),
+            element("arm", "108", None, false),
+            element("lever", "116", None, false),
+            element("cover", "130", None, false),
+            element("pin", "240", None, false),
+        ]);
+        let unsure = |text: &str, x: f32| OcrResult { confidence: 0.3, ..reading(text, x) };
+        let pages = vec![(1, RgbImage::new(1, 1), vec![
+            reading("102", 0.1),
+            reading("1O8", 0.2),
+            reading("118", 0.3),
+            unsure("245", 0.4),
+            unsure("204", 0.5),
+        ])];
+        let report = cross_check(&pages, &docx, &LabelGrammar::default()).unwrap();
+        assert_eq!(numerals(&report.matches, |n| &n.numeral), vec!["102"]);
+        assert_eq!(numerals(&report.drawing_only, |n| &n.numeral), vec!["118"]);
+        assert_eq!(numerals(&report.spec_only, |n| &n.numeral), vec!["116", "130"]);
+        assert_eq!(report.needs_review.len(), 3);
+        assert!(matches!(&report.needs_review[0], ReviewItem::ProbableMisread { read_as, numeral, .. } if read_as == "1O8" && numeral == "108"));
+        assert!(matches!(&report.needs_review[1], ReviewItem::LowConfidence { numeral, .. } if numeral == "204"));
+        assert!(matches!(&report.needs_review[2], ReviewItem::ProbableMisread { read_as, numeral, .. } if read_as == "245" && numeral == "240"));
+        let suggested: Vec<(&str, &[String])> = report.suggestions.iter().map(|s| (s.read_as.as_str(), s.numerals.as_slice())).collect();
+        assert_eq!(suggested, vec![("1O8", &["108".to_string()][..]), ("118", &["108".to_string()][..]), ("245", &["240".to_string()][..])]);
+    }
+
+    #[test]
+    fn single_character_differences() {
+        assert_eq!(single_difference("1O8", "108"), Some((Some('o'), '0')));
+        assert_eq!(single_difference("1108", "108"), Some((None, '1')));
+        assert_eq!(single_difference("108", "108a"), None);
+        assert_eq!(single_difference("108", "180"), None);
+        assert_eq!(single_difference("108", "108"), None);
+    }
+
+    #[test]
+    fn misreads_need_a_confusion_pair_or_low_confidence() {
+        assert!(is_probable_misread("1O8", "108", false));
+        assert!(is_probable_misread("S02", "502", false));
+        assert!(is_probable_misread("1l0", "110", false));
+        assert!(!is_probable_misread("116", "118", false));
+        assert!(is_probable_misread("116", "118", true));
+        assert!(!is_probable_misread("1108", "108", false));
+        assert!(is_probable_misread("1108", "108", true));
+        assert!(!is_probable_misread("120a", "120", true));
+    }
+}
@@ -0,0 +1,575 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use docx_rs::{
+    CommentChild, CommentRangeStart, Delete, DeleteChild, DeleteText, DocumentChild, DrawingData, FooterChild,
+    HeaderChild, InsertChild, MoveFromChild, MoveToChild, Paragraph, ParagraphChild, Run, RunChild,
+    StructuredDataTag, StructuredDataTagChild, Table, TableCellContent, TableChild, TableRowChild, TextBoxContentChild,
+};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::spec::{ParagraphOrigin, RevisionView, SpecParagraph};
+
+/// Paragraphs of each footnote, by footnote id
+type Footnotes = BTreeMap<usize, Vec<String>>;
+
+/// Ids of the footnotes each paragraph cites, by paragraph id
+type Citations = HashMap<String, Vec<usize>>;
+
+/// Marks around inserted and deleted text when tracked changes are shown with markup.
+/// They are words of their own, so element names and numerals never run into them.
+const INSERTION_MARKS: (&str, &str) = ("[+", "+]");
+const DELETION_MARKS: (&str, &str) = ("[-", "-]");
+
+/// Collects the paragraphs of a DOCX in reading order
+struct DocxReader<'a> {
+    revisions: RevisionView,
+    footnotes: &'a Footnotes,
+    citations: &'a Citations,
+    cited: Vec<usize>,  // Footnotes already placed after the paragraph citing them
+    paragraphs: Vec<SpecParagraph>,
+}
+
+impl DocxReader<'_> {
+    /// Read a paragraph, followed by the text boxes and footnotes anchored in it
+    fn paragraph(&mut self, para: &Paragraph, origin: ParagraphOrigin) {
+        let mut runs = Vec::new();
+        let mut anchored = Vec::new();
+        self.inline(&para.children, origin, &mut runs, &mut anchored);
+        for id in self.citations.get(&para.id).into_iter().flatten() {
+            if let Some(note) = self.footnotes.get(id) {
+                self.cited.push(*id);
+                anchored.extend(note.iter().map(|text| SpecParagraph::new(text.as_str(), ParagraphOrigin::Footnote)));
+            }
+        }
+
+        let para_text = join_runs(&runs);
+        println!("[DEBUG] Paragraph text after joining: {}", para_text);
+        self.paragraphs.push(SpecParagraph::new(para_text, origin));
+        self.paragraphs.append(&mut anchored);
+    }
+
+    /// Read the content of a paragraph, including tracked changes, hyperlinks and inline content controls.
+    /// Each run adds one piece of text; inserted and deleted text is kept as the revision view asks.
+    fn inline(&mut self, children: &[ParagraphChild], origin: ParagraphOrigin, runs: &mut Vec<String>, anchored: &mut Vec<SpecParagraph>) {
+        for child in children {
+            match child {
+                ParagraphChild::Run(run) => runs.push(self.run(run, anchored)),
+                ParagraphChild::Insert(insert) if self.revisions != RevisionView::Original => {
+                    self.mark(INSERTION_MARKS.0, runs);
+                    for child in &insert.children {
+                        match child {
+                            InsertChild::Run(run) => runs.push(self.run(run, anchored)),
+                            InsertChild::Delete(delete) => self.deleted(delete, runs, anchored),
+                            InsertChild::CommentStart(comment) => self.comment(comment, anchored),
+                            _ => {}
+                        }
+                    }
+                    self.mark(INSERTION_MARKS.1, runs);
+                }
+                ParagraphChild::MoveTo(move_to) if self.revisions != RevisionView::Original => {
+                    self.mark(INSERTION_MARKS.0, runs);
+                    for child in &move_to.children {
+                        match child {
+                            MoveToChild::Run(run) => runs.push(self.run(run, anchored)),
+                            MoveToChild::Delete(delete) => self.deleted(delete, runs, anchored),
+                            MoveToChild::CommentStart(comment) => self.comment(comment, anchored),
+                            _ => {}
+                        }
+                    }
+                    self.mark(INSERTION_MARKS.1, runs);
+                }
+                ParagraphChild::Delete(delete) => self.deleted(delete, runs, anchored),
+                ParagraphChild::MoveFrom(move_from) if self.revisions != RevisionView::Final => {
+                    self.mark(DELETION_MARKS.0, runs);
+                    for child in &move_from.children {
+                        match child {
+                            MoveFromChild::Run(run) => runs.push(self.run(run, anchored)),
+                            MoveFromChild::CommentStart(comment) => self.comment(comment, anchored),
+                            _ => {}
+                        }
+                    }
+                    self.mark(DELETION_MARKS.1, runs);
+                }
+                ParagraphChild::CommentStart(comment) => self.comment(comment, anchored),
+                ParagraphChild::Hyperlink(link) => self.inline(&link.children, origin, runs, anchored),
+                ParagraphChild::StructuredDataTag(sdt) => self.inline_structured_data_tag(sdt, origin, runs, anchored),
+                _ => {}
+            }
+        }
+    }
+
+    /// Read tracked deleted text, unless the final version is wanted
+    fn deleted(&mut self, delete: &Delete, runs: &mut Vec<String>, anchored: &mut Vec<SpecParagraph>) {
+        if self.revisions == RevisionView::Final {
+            return;
+        }
+        self.mark(DELETION_MARKS.0, runs);
+        for child in &delete.children {
+            match child {
+                DeleteChild::Run(run) => runs.push(self.run(run, anchored)),
+                DeleteChild::CommentStart(comment) => self.comment(comment, anchored),
+                _ => {}
+            }
+        }
+        self.mark(DELETION_MARKS.1, runs);
+    }
+
+    /// Mark where an insertion or deletion starts or ends, when showing markup
+    fn mark(&self, mark: &str, runs: &mut Vec<String>) {
+        if self.revisions == RevisionView::Markup {
+            runs.push(mark.to_string());
+        }
+    }
+
+    /// Read the comment anchored at a comment range, only when showing markup
+    fn comment(&mut self, start: &CommentRangeStart, anchored: &mut Vec<SpecParagraph>) {
+        if self.revisions != RevisionView::Markup {
+            return;
+        }
+        for child in &start.comment.children {
+            match child {
+                CommentChild::Paragraph(para) => anchored.extend(self.detached(|reader| reader.paragraph(para, ParagraphOrigin::Comment))),
+                CommentChild::Table(table) => anchored.extend(self.detached(|reader| reader.table(table, ParagraphOrigin::Comment))),
+            }
+        }
+    }
+
+    /// Content control inside a paragraph. Its runs continue the paragraph; any paragraphs
+    /// or tables in it are anchored in the paragraph like text boxes.
+    fn inline_structured_data_tag(&mut self, sdt: &StructuredDataTag, origin: ParagraphOrigin, runs: &mut Vec<String>, anchored: &mut Vec<SpecParagraph>) {
+        for child in &sdt.children {
+            match child {
+                StructuredDataTagChild::Run(run) => runs.push(self.run(run, anchored)),
+                StructuredDataTagChild::StructuredDataTag(sdt) => self.inline_structured_data_tag(sdt, origin, runs, anchored),
+                StructuredDataTagChild::Paragraph(para) => anchored.extend(self.detached(|reader| reader.paragraph(para, origin))),
+                StructuredDataTagChild::Table(table) => anchored.extend(self.detached(|reader| reader.table(table, origin))),
+                _ => {}
+            }
+        }
+    }
+
+    /// Paragraphs read by `read`, kept apart so they can be placed after the paragraph being read
+    fn detached(&mut self, read: impl FnOnce(&mut Self)) -> Vec<SpecParagraph> {
+        let outer = std::mem::take(&mut self.paragraphs);
+        read(self);
+        std::mem::replace(&mut self.paragraphs, outer)
+    }
+
+    /// Text of a run. Text boxes drawn in it are added to `anchored`.
+    fn run(&mut self, run: &Run, anchored: &mut Vec<SpecParagraph>) -> String {
+        let mut text = String::new();
+        for child in &run.children {
+            match child {
+                RunChild::Text(t) => text.push_str(&t.text),
+                RunChild::DeleteText(deleted) => text.push_str(&deleted_text(deleted)),
+                RunChild::Tab(_) | RunChild::PTab(_) | RunChild::Break(_) | RunChild::CarriageReturn(_) => text.push(' '),
+                RunChild::Drawing(drawing) => {
+                    if let Some(DrawingData::TextBox(text_box)) = &drawing.data {
+                        anchored.extend(self.detached(|reader| {
+                            for child in &text_box.children {
+                                match child {
+                                    TextBoxContentChild::Paragraph(para) => reader.paragraph(para, ParagraphOrigin::TextBox),
+                                    TextBoxContentChild::Table(table) => reader.table(table, ParagraphOrigin::TextBox),
+                                }
+                            }
+                        }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        text
+    }
+
+    /// Read every cell of a table, row by row, including nested tables
+    fn table(&mut self, table: &Table, origin: ParagraphOrigin) {
+        for TableChild::TableRow(row) in &table.rows {
+            for TableRowChild::TableCell(cell) in &row.cells {
+                for content in &cell.children {
+                    match content {
+                        TableCellContent::Paragraph(para) => self.paragraph(para, origin),
+                        TableCellContent::Table(table) => self.table(table, origin),
+                        TableCellContent::StructuredDataTag(sdt) => self.structured_data_tag(sdt, origin),
+                        TableCellContent::TableOfContents(_) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Read a block content control, whose paragraphs belong to the part that holds it
+    fn structured_data_tag(&mut self, sdt: &StructuredDataTag, origin: ParagraphOrigin) {
+        let mut runs = Vec::new();
+        let mut anchored = Vec::new();
+        for child in &sdt.children {
+            match child {
+                StructuredDataTagChild::Paragraph(para) => self.paragraph(para, origin),
+                StructuredDataTagChild::Table(table) => self.table(table, origin),
+                StructuredDataTagChild::StructuredDataTag(sdt) => self.structured_data_tag(sdt, origin),
+                StructuredDataTagChild::Run(run) => runs.push(self.run(run, &mut anchored)),
+                _ => {}
+            }
+        }
+        if !runs.is_empty() {
+            self.paragraphs.push(SpecParagraph::new(join_runs(&runs), origin));
+            self.paragraphs.append(&mut anchored);
+        }
+    }
+}
+
+/// Join the runs of a paragraph as Word shows them: runs carry their own spacing, so they are
+/// concatenated as they are, and only the markup marks are set apart as words of their own.
+fn join_runs(runs: &[String]) -> String {
+    let is_mark = |run: &str| [INSERTION_MARKS.0, INSERTION_MARKS.1, DELETION_MARKS.0, DELETION_MARKS.1].contains(&run);
+    let mut text = String::new();
+    let mut after_mark = false;
+    for run in runs {
+        let separate = after_mark || is_mark(run);
+        if separate && !text.is_empty() && !text.ends_with(char::is_whitespace) && !run.starts_with(char::is_whitespace) {
+            text.push(' ');
+        }
+        text.push_str(run);
+        after_mark = is_mark(run);
+    }
+    text
+}
+
+/// Text of a tracked deletion. docx-rs keeps it private, but serializes it.
+fn deleted_text(deleted: &DeleteText) -> String {
+    serde_json::to_value(deleted).ok()
+        .and_then(|value| value["text"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Read a part of a DOCX package as text, or None when the package lacks it
+fn read_part(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let mut content = String::new();
+    match archive.by_name(name) {
+        Ok(mut file) => file.read_to_string(&mut content).with_context(|| format!("Failed to read {}", name))?,
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(content))
+}
+
+/// Value of a w: attribute as a number
+fn numeric_attribute(e: &quick_xml::events::BytesStart, name: &str) -> Result<Option<usize>> {
+    Ok(e.try_get_attribute(name)?
+        .and_then(|value| std::str::from_utf8(&value.value).ok()?.parse().ok()))
+}
+
+/// Footnotes cited in each paragraph of word/document.xml, by paragraph id. docx-rs keeps the
+/// paragraph ids but drops footnote references, so they are found in the XML directly.
+fn read_citations(document: &str) -> Result<Citations> {
+    let mut reader = Reader::from_str(document);
+    let mut citations = Citations::new();
+    let mut open: Vec<String> = Vec::new();  // Ids of the paragraphs being read, innermost last
+
+    loop {
+        match reader.read_event().context("Failed to parse DOCX document")? {
+            Event::Start(e) if e.name().as_ref() == b"w:p" => {
+                let id = e.try_get_attribute("w14:paraId")?
+                    .map(|id| String::from_utf8_lossy(&id.value).into_owned())
+                    .unwrap_or_default();
+                open.push(id);
+            }
+            Event::End(e) if e.name().as_ref() == b"w:p" => {
+                open.pop();
+            }
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"w:footnoteReference" => {
+                if let (Some(paragraph), Some(id)) = (open.last(), numeric_attribute(&e, "w:id")?) {
+                    if !paragraph.is_empty() {
+                        citations.entry(paragraph.clone()).or_default().push(id);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(citations)
+}
+
+/// Read the paragraphs of each footnote from word/footnotes.xml, which docx-rs does not read.
+/// Tracked changes are kept as `revisions` asks. The separator notes Word adds have no text and are dropped.
+fn read_footnotes(content: &str, revisions: RevisionView) -> Result<Footnotes> {
+    let mut reader = Reader::from_str(content);
+    let mut footnotes = Footnotes::new();
+    let mut note: Option<(usize, Vec<String>)> = None;
+    let mut in_text = false;  // Inside a w:t or w:delText, as opposed to field codes
+    let mut in_insertion = false;
+    let mut in_deletion = false;
+
+    loop {
+        let shown = match revisions {
+            RevisionView::Final => !in_deletion,
+            RevisionView::Original => !in_insertion,
+            RevisionView::Markup => true,
+        };
+        let paragraph = note.as_mut().and_then(|(_, paragraphs)| paragraphs.last_mut());
+
+        match reader.read_event().context("Failed to parse DOCX footnotes")? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"w:footnote" => note = numeric_attribute(&e, "w:id")?.map(|id| (id, Vec::new())),
+                b"w:p" => {
+                    if let Some((_, paragraphs)) = &mut note {
+                        paragraphs.push(String::new());
+                    }
+                }
+                b"w:t" | b"w:delText" => in_text = true,
+                name @ (b"w:ins" | b"w:moveTo" | b"w:del" | b"w:moveFrom") => {
+                    let insertion = matches!(name, b"w:ins" | b"w:moveTo");
+                    if insertion {
+                        in_insertion = true;
+                    } else {
+                        in_deletion = true;
+                    }
+                    if let (RevisionView::Markup, Some(paragraph)) = (revisions, paragraph) {
+                        let mark = if insertion { INSERTION_MARKS.0 } else { DELETION_MARKS.0 };
+                        paragraph.push_str(&format!(" {} ", mark));
+                    }
+                }
+                _ => {}
+            },
+            Event::End(e) => match e.name().as_ref() {
+                b"w:footnote" => {
+                    if let Some((id, paragraphs)) = note.take() {
+                        footnotes.insert(id, paragraphs.into_iter().filter(|p| !p.trim().is_empty()).collect());
+                    }
+                }
+                b"w:t" | b"w:delText" => in_text = false,
+                name @ (b"w:ins" | b"w:moveTo" | b"w:del" | b"w:moveFrom") => {
+                    let insertion = matches!(name, b"w:ins" | b"w:moveTo");
+                    if insertion {
+                        in_insertion = false;
+                    } else {
+                        in_deletion = false;
+                    }
+                    if let (RevisionView::Markup, Some(paragraph)) = (revisions, paragraph) {
+                        let mark = if insertion { INSERTION_MARKS.1 } else { DELETION_MARKS.1 };
+                        paragraph.push_str(&format!(" {} ", mark));
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(e) if matches!(e.name().as_ref(), b"w:tab" | b"w:br" | b"w:cr") && shown => {
+                if let Some(paragraph) = paragraph {
+                    paragraph.push(' ');
+                }
+            }
+            Event::Text(e) if in_text && shown => {
+                if let Some(paragraph) = paragraph {
+                    paragraph.push_str(&e.decode()?);
+                }
+            }
+            Event::GeneralRef(e) if in_text && shown => {
+                if let Some(paragraph) = paragraph {
+                    if let Some(c) = e.resolve_char_ref()? {
+                        paragraph.push(c);
+                    } else if let Some(entity) = quick_xml::escape::resolve_predefined_entity(&e.decode()?) {
+                        paragraph.push_str(entity);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(footnotes)
+}
+
+/// Read every paragraph of a DOCX: the body with its tables, content controls and text boxes,
+/// then footnotes no paragraph is known to cite, then the headers and footers of the last section.
+/// Tracked changes are read as `revisions` asks.
+pub fn paragraphs(path: &Path, revisions: RevisionView) -> Result<Vec<SpecParagraph>> {
+    let docx_content = std::fs::read(path)
+        .context("Failed to read DOCX file")?;
+    let docx = docx_rs::read_docx(&docx_content)
+        .context("Failed to parse DOCX file")?;
+    let mut archive = zip::ZipArchive::new(File::open(path).context("Failed to open DOCX file")?)
+        .context("Failed to read DOCX archive")?;
+    let footnotes = match read_part(&mut archive, "word/footnotes.xml")? {
+        Some(content) => read_footnotes(&content, revisions)?,
+        None => Footnotes::new(),
+    };
+    let citations = match read_part(&mut archive, "word/document.xml")? {
+        Some(document) if !footnotes.is_empty() => read_citations(&document)?,
+        _ => Citations::new(),
+    };
+
+    println!("[DEBUG] Reading DOCX tracked changes in {:?} view", revisions);
+    let mut reader = DocxReader { revisions, footnotes: &footnotes, citations: &citations, cited: Vec::new(), paragraphs: Vec::new() };
+    for child in &docx.document.children {
+        match child {
+            DocumentChild::Paragraph(para) => reader.paragraph(para, ParagraphOrigin::Body),
+            DocumentChild::Table(table) => reader.table(table, ParagraphOrigin::TableCell),
+            DocumentChild::StructuredDataTag(sdt) => reader.structured_data_tag(sdt, ParagraphOrigin::Body),
+            _ => {}
+        }
+    }
+
+    for (id, note) in &footnotes {
+        if !reader.cited.contains(id) {
+            reader.paragraphs.extend(note.iter().map(|text| SpecParagraph::new(text.as_str(), ParagraphOrigin::Footnote)));
+        }
+    }
+
+    let section = &docx.document.section_property;
+    for (_, header) in [&section.header, &section.first_header, &section.even_header].into_iter().flatten() {
+        for child in &header.children {
+            match child {
+                HeaderChild::Paragraph(para) => reader.paragraph(para, ParagraphOrigin::Header),
+                HeaderChild::Table(table) => reader.table(table, ParagraphOrigin::Header),
+                HeaderChild::StructuredDataTag(sdt) => reader.structured_data_tag(sdt, ParagraphOrigin::Header),
+            }
+        }
+    }
+    for (_, footer) in [&section.footer, &section.first_footer, &section.even_footer].into_iter().flatten() {
+        for child in &footer.children {
+            match child {
+                FooterChild::Paragraph(para) => reader.paragraph(para, ParagraphOrigin::Footer),
+                FooterChild::Table(table) => reader.table(table, ParagraphOrigin::Footer),
+                FooterChild::StructuredDataTag(sdt) => reader.structured_data_tag(sdt, ParagraphOrigin::Footer),
+            }
+        }
+    }
+
+    Ok(reader.paragraphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use docx_rs::{Docx, Footer, Footnote, Header, Insert, TableCell, TableRow};
+
+    /// Text box drawing in place of a run's text, since docx-rs cannot write text boxes
+    const TEXT_BOX: &str = concat!(
+        "<w:drawing><wp:inline><a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\"><a:graphicData>",
+        "<wps:wsp><wps:txbx><w:txbxContent><w:p><w:r><w:t>lever 112</w:t></w:r></w:p></w:txbxContent></wps:txbx></wps:wsp>",
+        "</a:graphicData></a:graphic></wp:inline></w:drawing>",
+    );
+
+    /// Write a document and read its paragraphs, with any run reading "TEXT BOX" turned into a text box
+    fn read_docx(name: &str, docx: Docx) -> Vec<(String, ParagraphOrigin)> {
+        let mut packed = std::io::Cursor::new(Vec::new());
+        docx.build().pack(&mut packed).unwrap();
+        let mut built = zip::ZipArchive::new(packed).unwrap();
+        let path = std::env::temp_dir().join(format!("docx_{}_{}.docx", std::process::id(), name));
+        let mut archive = zip::ZipWriter::new(File::create(&path).unwrap());
+        for index in 0..built.len() {
+            let mut part = built.by_index(index).unwrap();
+            let mut content = String::new();
+            part.read_to_string(&mut content).unwrap();
+            if part.name() == "word/document.xml" {
+                content = content.replace("<w:t xml:space=\"preserve\">TEXT BOX</w:t>", TEXT_BOX);
+            }
+            archive.start_file(part.name(), zip::write::SimpleFileOptions::default()).unwrap();
+            std::io::Write::write_all(&mut archive, content.as_bytes()).unwrap();
+        }
+        archive.finish().unwrap();
+        let read = paragraphs(&path, RevisionView::Final).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        read.into_iter().map(|paragraph| (paragraph.text, paragraph.origin)).collect()
+    }
+
+    fn text(text: &str) -> Paragraph {
+        Paragraph::new().add_run(Run::new().add_text(text))
+    }
+
+    fn texts(runs: &[&str]) -> Vec<String> {
+        runs.iter().map(|run| run.to_string()).collect()
+    }
+
+    #[test]
+    fn runs_are_concatenated_as_written() {
+        assert_eq!(join_runs(&texts(&["The hous", "ing ", "10", "2 has"])), "The housing 102 has");
+    }
+
+    #[test]
+    fn marks_are_separate_words() {
+        assert_eq!(join_runs(&texts(&["10", "[-", "6", "-]", "[+", "8", "+]", " arms"])), "10 [- 6 -] [+ 8 +] arms");
+        assert_eq!(join_runs(&texts(&["a ", "[+", "hinge", "+]"])), "a [+ hinge +]");
+    }
+
+    #[test]
+    fn tracked_change_inside_a_numeral() {
+        let path = std::env::temp_dir().join(format!("docx_runs_{}.docx", std::process::id()));
+        Docx::new()
+            .add_paragraph(Paragraph::new()
+                .add_run(Run::new().add_text("The arm 10"))
+                .add_delete(Delete::new().add_run(Run::new().add_delete_text("6")))
+                .add_insert(Insert::new(Run::new().add_text("8")))
+                .add_run(Run::new().add_text(" pivots.")))
+            .build()
+            .pack(File::create(&path).unwrap())
+            .unwrap();
+        let text = |revisions| paragraphs(&path, revisions).unwrap()[0].text.clone();
+        assert_eq!(text(RevisionView::Final), "The arm 108 pivots.");
+        assert_eq!(text(RevisionView::Original), "The arm 106 pivots.");
+        assert_eq!(text(RevisionView::Markup), "The arm 10 [- 6 -] [+ 8 +] pivots.");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn every_part_is_read_with_its_origin() {
+        let docx = Docx::new()
+            .header(Header::new().add_paragraph(text("Docket 500")))
+            .footer(Footer::new().add_paragraph(text("Page 1")))
+            .add_paragraph(Paragraph::new()
+                .add_run(Run::new().add_text("The housing 102"))
+                .add_run(Run::new().add_footnote_reference(Footnote::new().add_content(text("See lever 104."))))
+                .add_run(Run::new().add_text(" holds.")))
+            .add_paragraph(text("A later paragraph."))
+            .add_table(Table::new(vec![TableRow::new(vec![TableCell::new().add_paragraph(text("pin 106"))])]))
+            .add_structured_data_tag(StructuredDataTag::new().add_paragraph(text("cover 108")))
+            .add_paragraph(Paragraph::new()
+                .add_run(Run::new().add_text("The frame 110"))
+                .add_run(Run::new().add_text("TEXT BOX")));
+        let expected: Vec<(String, ParagraphOrigin)> = [
+            ("The housing 102 holds.", ParagraphOrigin::Body),
+            ("See lever 104.", ParagraphOrigin::Footnote),
+            ("A later paragraph.", ParagraphOrigin::Body),
+            ("pin 106", ParagraphOrigin::TableCell),
+            ("cover 108", ParagraphOrigin::Body),
+            ("The frame 110", ParagraphOrigin::Body),
+            ("lever 112", ParagraphOrigin::TextBox),
+            ("Docket 500", ParagraphOrigin::Header),
+            ("Page 1", ParagraphOrigin::Footer),
+        ].iter().map(|(text, origin)| (text.to_string(), *origin)).collect();
+        assert_eq!(read_docx("parts", docx), expected);
+    }
+
+    #[test]
+    fn citations_are_found_by_paragraph_id() {
+        let document = concat!(
+            "<w:document><w:body>",
+            "<w:p w14:paraId=\"0A\"><w:r><w:t>arm</w:t></w:r><w:r><w:footnoteReference w:id=\"2\"/></w:r><w:r><w:footnoteReference w:id=\"3\"/></w:r></w:p>",
+            "<w:p w14:paraId=\"0B\"><w:r><w:t>no notes</w:t></w:r></w:p>",
+            "<w:p><w:r><w:footnoteReference w:id=\"4\"/></w:r></w:p>",
+            "</w:body></w:document>",
+        );
+        let citations = read_citations(document).unwrap();
+        assert_eq!(citations, Citations::from([("0A".to_string(), vec![2, 3])]));
+    }
+
+    #[test]
+    fn footnotes_follow_the_revision_view() {
+        let content = concat!(
+            "<w:footnotes>",
+            "<w:footnote w:type=\"separator\" w:id=\"-1\"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>",
+            "<w:footnote w:id=\"2\">",
+            "<w:p><w:r><w:t>Lever 10</w:t></w:r><w:del><w:r><w:delText>6</w:delText></w:r></w:del>",
+            "<w:ins><w:r><w:t>4</w:t></w:r></w:ins><w:r><w:t xml:space=\"preserve\"> &amp; pin</w:t></w:r></w:p>",
+            "<w:p><w:r><w:t>second</w:t><w:tab/><w:t>106</w:t></w:r></w:p>",
+            "</w:footnote>",
+            "</w:footnotes>",
+        );
+        let notes = |revisions| read_footnotes(content, revisions).unwrap();
+        assert_eq!(notes(RevisionView::Final), Footnotes::from([(2, vec!["Lever 104 & pin".to_string(), "second 106".to_string()])]));
+        assert_eq!(notes(RevisionView::Original)[&2][0], "Lever 106 & pin");
+    }
+}
@@ -0,0 +1,398 @@
+use crate::grammar::is_prime_mark;
+use crate::normalize_text;
+
+/// Longest element name we will walk back over, e.g. "first upper support arm"
+const MAX_NAME_WORDS: usize = 4;
+
+/// Leading words that are dropped from an element name
+static DETERMINERS: &[&str] = &[
+    "a", "an", "the", "said", "each", "this", "that", "these", "those", "its", "their",
+    "another", "any", "every", "such", "one", "both", "either", "respective",
+    "all", "some", "no", "more", "other", "various", "multiple", "several", "many",
+    "two", "three", "four",
+];
+
+/// Words that can never be part of an element name; walking back stops at them
+static STOP_WORDS: &[&str] = &[
+    // Prepositions
+    "of", "to", "in", "on", "at", "by", "with", "from", "into", "onto", "for", "via",
+    "through", "between", "within", "about", "over", "under", "against", "along", "across",
+    // Conjunctions and relatives
+    "and", "or", "nor", "but", "than", "as", "which", "who", "where", "wherein", "whereby",
+    "when", "while", "then", "if", "also", "thereby", "thus", "just", "only", "further",
+    // Common verbs around numerals
+    "is", "are", "was", "were", "be", "been", "being", "has", "have", "had", "may", "can",
+    "include", "includes", "including", "comprise", "comprises", "comprising", "having",
+    "see", "shown", "illustrated", "depicted", "labeled", "designated",
+    // Figure and numeral references
+    "fig", "figs", "figure", "figures", "reference", "numeral", "numerals", "approximately",
+    "claim", "claims",
+];
+
+/// Whether a word joins two numerals of the same element, as in "arm 104 and 106"
+pub fn is_conjunction(word: &str) -> bool {
+    word.eq_ignore_ascii_case("and") || word.eq_ignore_ascii_case("or")
+}
+
+/// Largest span a numeric range is expanded over, e.g. "120-170"
+const MAX_RANGE: u32 = 50;
+
+/// Words that turn two numerals into an inclusive range, e.g. "120 through 124"
+static RANGE_WORDS: &[&str] = &["through", "thru", "to"];
+
+/// Strip surrounding punctuation from a numeral token, e.g. "(104)," -> "104".
+/// Prime marks are kept, as in "102′", and so is a closing parenthesis that
+/// belongs to the numeral, as in "130(n)".
+pub fn trim_numeral(word: &str) -> &str {
+    let trimmed = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let trimmed = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric() && c != ')' && !is_prime_mark(c));
+    if trimmed.contains('(') {
+        trimmed
+    } else {
+        trimmed.trim_end_matches(|c: char| !c.is_alphanumeric() && !is_prime_mark(c))
+    }
+}
+
+/// Whether a word is a numeral that can continue a list, e.g. "110b," or "(106)"
+fn is_numeral_token(word: &str) -> bool {
+    trim_numeral(word).starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Split a numeral into its leading digits and whatever follows, e.g. "104a" -> ("104", "a")
+fn split_base(numeral: &str) -> (&str, &str) {
+    let end = numeral.find(|c: char| !c.is_ascii_digit()).unwrap_or(numeral.len());
+    numeral.split_at(end)
+}
+
+/// Whether a numeral carries a parenthesized index, e.g. "130(1)" or "130(n)"
+pub fn is_parenthesized(numeral: &str) -> bool {
+    let (base, suffix) = split_base(numeral);
+    !base.is_empty() && suffix.starts_with('(') && suffix.ends_with(')')
+}
+
+/// Expand an inclusive range such as "120-124", "104a-104c", "104a-c" or "130(1)-130(3)".
+/// Returns None when the two ends do not form a range, so "130-1" stays a single sub-numeral.
+pub fn expand_range(start: &str, end: &str) -> Option<Vec<String>> {
+    let (start_base, start_suffix) = split_base(start);
+    if start_base.is_empty() {
+        return None;
+    }
+
+    // "104a-c" is shorthand that repeats the base of the start
+    let (end_base, end_suffix) = match split_base(end) {
+        ("", suffix) => (start_base, suffix),
+        parts => parts,
+    };
+
+    // Plain numbers need the same number of digits on both ends
+    if start_suffix.is_empty() && end_suffix.is_empty() {
+        if start_base.len() != end_base.len() {
+            return None;
+        }
+        let first = start_base.parse::<u32>().ok()?;
+        let last = end_base.parse::<u32>().ok()?;
+        if last <= first || last - first > MAX_RANGE {
+            return None;
+        }
+        return Some((first..=last).map(|n| n.to_string()).collect());
+    }
+
+    if start_base != end_base {
+        return None;
+    }
+
+    // Letter suffixes, e.g. 110a-110c
+    let letters = |suffix: &str| {
+        let mut chars = suffix.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphabetic() => Some(c),
+            _ => None,
+        }
+    };
+    if let (Some(first), Some(last)) = (letters(start_suffix), letters(end_suffix)) {
+        if last <= first {
+            return None;
+        }
+        return Some((first..=last).map(|c| format!("{}{}", start_base, c)).collect());
+    }
+
+    // Hyphenated sub-numerals, e.g. 150-1 through 150-3
+    let sub_number = |suffix: &str| suffix.strip_prefix('-').and_then(|n| n.parse::<u32>().ok());
+    if let (Some(first), Some(last)) = (sub_number(start_suffix), sub_number(end_suffix)) {
+        if last <= first || last - first > MAX_RANGE {
+            return None;
+        }
+        return Some((first..=last).map(|n| format!("{}-{}", start_base, n)).collect());
+    }
+
+    // Parenthesized indexes, e.g. 130(1)-130(3); symbolic ends like 130(n) keep just the endpoints
+    if is_parenthesized(start) && end_suffix.starts_with('(') && end_suffix.ends_with(')') {
+        let end = format!("{}{}", start_base, end_suffix);
+        let index = |s: &str| s.trim_start_matches('(').trim_end_matches(')').parse::<u32>().ok();
+        return match (index(start_suffix), index(end_suffix)) {
+            (Some(first), Some(last)) if last > first && last - first <= MAX_RANGE => {
+                Some((first..=last).map(|n| format!("{}({})", start_base, n)).collect())
+            }
+            _ => Some(vec![start.to_string(), end]),
+        };
+    }
+
+    None
+}
+
+/// Split a single token into the ends of a range, e.g. "120-124" -> ("120", Some("124"))
+fn split_range_token(token: &str) -> (String, Option<String>) {
+    for (i, c) in token.char_indices() {
+        if c == '-' || c == '\u{2013}' {
+            let (start, end) = (&token[..i], &token[i + c.len_utf8()..]);
+            if expand_range(start, end).is_some() {
+                return (start.to_string(), Some(end.to_string()));
+            }
+        }
+    }
+    (token.to_string(), None)
+}
+
+/// Collect the numerals listed from `index` onwards, e.g. "110a, 110b, and 110c" or
+/// "120 through 124". Returns each entry as a start and optional range end, together
+/// with the index of the last word consumed.
+pub fn numeral_list(words: &[&str], index: usize) -> (Vec<(String, Option<String>)>, usize) {
+    let mut items = vec![split_range_token(trim_numeral(words[index]))];
+    let mut last = index;
+
+    loop {
+        let mut next = last + 1;
+        let mut range = false;
+
+        // A comma, "and"/"or", or a range word may join the next numeral
+        if next < words.len() && is_conjunction(words[next]) {
+            next += 1;
+        } else if next < words.len() && RANGE_WORDS.contains(&words[next].to_lowercase().as_str()) {
+            range = true;
+            next += 1;
+        } else if !words[last].ends_with(',') {
+            break;
+        }
+
+        if next >= words.len() || !is_numeral_token(words[next]) {
+            break;
+        }
+
+        let item = trim_numeral(words[next]);
+        match items.last_mut() {
+            Some((start, end @ None)) if range && expand_range(start, item).is_some() => {
+                *end = Some(item.to_string());
+            }
+            _ if range => break,
+            _ => items.push(split_range_token(item)),
+        }
+        last = next;
+    }
+
+    (items, last)
+}
+
+/// Expand one list entry into the individual numerals it stands for
+pub fn expand_item(start: &str, end: Option<&str>) -> Vec<String> {
+    match end.and_then(|end| expand_range(start, end)) {
+        Some(numerals) => numerals,
+        None => vec![start.to_string()],
+    }
+}
+
+/// Walk back from the numeral at `index` and collect the element name in front of it.
+/// Determiners are stripped and adjectives like "first" or "second" are kept, so
+/// "the first support arm 104" yields ["first", "support", "arm"].
+pub fn element_name_before<'a>(words: &[&'a str], index: usize) -> Vec<&'a str> {
+    let mut name = Vec::new();
+
+    for word in words[..index].iter().rev() {
+        // Punctuation after a word ends the phrase, e.g. "housing, 102" or "FIG. 10"
+        if word.ends_with(|c: char| ",;:.)(".contains(c)) {
+            break;
+        }
+        let bare = word.trim_start_matches(['(', '"']);
+        if bare.is_empty() || !bare.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'') {
+            break;
+        }
+        let lower = bare.to_lowercase();
+        if DETERMINERS.contains(&lower.as_str()) || STOP_WORDS.contains(&lower.as_str()) {
+            break;
+        }
+        name.insert(0, bare);
+        if name.len() == MAX_NAME_WORDS || bare.len() != word.len() {
+            break;
+        }
+    }
+
+    name
+}
+
+/// Verbs ending in "-ing" that follow an element name in claims, e.g. "a flange extending from"
+static TRAILING_VERBS: &[&str] = &[
+    "extending", "defining", "connecting", "coupling", "supporting", "holding", "receiving",
+    "engaging", "surrounding", "forming", "containing", "carrying", "projecting", "protruding",
+    "securing", "attaching", "biasing", "urging", "rotating", "enclosing", "abutting",
+];
+
+/// Verbs that end an element name in claims, e.g. "the arm rotates the lever"
+static PRESENT_VERBS: &[&str] = &[
+    "defines", "extends", "rotates", "engages", "receives", "moves", "surrounds", "connects",
+    "couples", "abuts", "carries", "protrudes", "biases", "urges", "secures", "retains",
+    "encloses", "contains", "provides", "allows", "permits", "causes", "enables",
+];
+
+/// Whether a word is a participle that can end an element name, e.g. "coupled" or "extending".
+/// Nouns like "housing" or "bearing" end in "-ing" too, so only listed "-ing" verbs count.
+fn is_participle(word: &str) -> bool {
+    (word.len() > 4 && word.ends_with("ed") && !word.ends_with("eed")) || TRAILING_VERBS.contains(&word)
+}
+
+/// Whether a word cannot continue an element name, e.g. "to" after "coupled"
+fn ends_name(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    DETERMINERS.contains(&lower.as_str()) || STOP_WORDS.contains(&lower.as_str())
+}
+
+/// Walk forward from `index` and collect the element name that starts there, e.g.
+/// "support arm coupled to ..." yields ["support", "arm"]. Determiners and stop words
+/// end the name, and so does punctuation after a word. A verb ends it too once the
+/// name has begun, though a participle only does so in front of a stop word, so
+/// "threaded fastener" and "connecting rod" stay whole.
+pub fn element_name_after<'a>(words: &[&'a str], index: usize) -> Vec<&'a str> {
+    let mut name = Vec::new();
+
+    for (position, word) in words.iter().enumerate().skip(index) {
+        let bare = word.trim_start_matches(['(', '"']).trim_end_matches(|c: char| ",;:.)\"".contains(c));
+        if bare.is_empty() || !bare.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'') || ends_name(bare) {
+            break;
+        }
+        let lower = bare.to_lowercase();
+        if !name.is_empty() {
+            let before_stop = bare.len() != word.len() || words.get(position + 1).is_none_or(|next| ends_name(next));
+            if PRESENT_VERBS.contains(&lower.as_str()) || (is_participle(&lower) && before_stop) {
+                break;
+            }
+        }
+        name.push(bare);
+        if name.len() == MAX_NAME_WORDS || bare.len() != word.len() {
+            break;
+        }
+    }
+
+    name
+}
+
+/// Normalize an element name for comparison; only the head noun is singularized
+/// so "first support arms" and "first support arm" compare equal
+pub fn normalize_element_name(name: &str) -> String {
+    let mut words: Vec<String> = name.split_whitespace().map(|w| w.to_lowercase()).collect();
+    if let Some(head) = words.last_mut() {
+        *head = normalize_text(head);
+    }
+    words.join(" ")
+}
+
+/// Qualifiers that distinguish otherwise identical elements, e.g. "first arm" vs "second arm"
+static DISTINGUISHING_QUALIFIERS: &[&str] = &[
+    "first", "second", "third", "fourth", "fifth", "sixth", "upper", "lower", "left", "right",
+    "inner", "outer", "front", "rear", "top", "bottom", "proximal", "distal",
+];
+
+/// Whether two normalized element names can describe the same element.
+/// "web" and "inflatable web" can; "housing" and "frame" cannot, and neither can
+/// "first support arm" and "second support arm".
+pub fn names_compatible(a: &str, b: &str) -> bool {
+    let a_words: Vec<&str> = a.split_whitespace().collect();
+    let b_words: Vec<&str> = b.split_whitespace().collect();
+    if a_words.last() != b_words.last() {
+        return false;
+    }
+
+    let qualifiers = |words: &[&str]| -> Vec<String> {
+        words.iter()
+            .filter(|w| DISTINGUISHING_QUALIFIERS.contains(w))
+            .map(|w| w.to_string())
+            .collect()
+    };
+    let a_qualifiers = qualifiers(&a_words);
+    let b_qualifiers = qualifiers(&b_words);
+    a_qualifiers.is_empty() || b_qualifiers.is_empty() || a_qualifiers == b_qualifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn plain_ranges_are_inclusive() {
+        assert_eq!(expand_range("120", "124"), Some(strings(&["120", "121", "122", "123", "124"])));
+        assert_eq!(expand_range("111", "115"), Some(strings(&["111", "112", "113", "114", "115"])));
+    }
+
+    #[test]
+    fn plain_ranges_need_matching_digits_and_order() {
+        assert_eq!(expand_range("99", "101"), None);
+        assert_eq!(expand_range("124", "120"), None);
+        assert_eq!(expand_range("100", "200"), None);
+    }
+
+    #[test]
+    fn suffixed_ranges() {
+        assert_eq!(expand_range("110a", "110c"), Some(strings(&["110a", "110b", "110c"])));
+        assert_eq!(expand_range("104a", "c"), Some(strings(&["104a", "104b", "104c"])));
+        assert_eq!(expand_range("150-1", "150-3"), Some(strings(&["150-1", "150-2", "150-3"])));
+        assert_eq!(expand_range("130(1)", "130(3)"), Some(strings(&["130(1)", "130(2)", "130(3)"])));
+        assert_eq!(expand_range("130(1)", "130(n)"), Some(strings(&["130(1)", "130(n)"])));
+        assert_eq!(expand_range("110a", "112c"), None);
+    }
+
+    #[test]
+    fn numeral_lists() {
+        let words = ["legs", "110a,", "110b,", "and", "110c", "extend"];
+        assert_eq!(numeral_list(&words, 1), (vec![("110a".into(), None), ("110b".into(), None), ("110c".into(), None)], 4));
+        let words = ["ribs", "120", "through", "124."];
+        assert_eq!(numeral_list(&words, 1), (vec![("120".into(), Some("124".into()))], 3));
+        let words = ["arm", "130-1", "moves"];
+        assert_eq!(numeral_list(&words, 1), (vec![("130-1".into(), None)], 1));
+    }
+
+    #[test]
+    fn names_before_numerals() {
+        let words: Vec<&str> = "the first support arm 104".split_whitespace().collect();
+        assert_eq!(element_name_before(&words, 4), vec!["first", "support", "arm"]);
+        let words: Vec<&str> = "see FIG. 10".split_whitespace().collect();
+        assert!(element_name_before(&words, 2).is_empty());
+    }
+
+    fn name_after(text: &str, index: usize) -> Vec<String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        element_name_after(&words, index).into_iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn names_after_articles() {
+        assert_eq!(name_after("a support arm coupled to the housing", 1), strings(&["support", "arm"]));
+        assert_eq!(name_after("a threaded fastener securing the arm", 1), strings(&["threaded", "fastener"]));
+        assert_eq!(name_after("the arm rotates the lever", 1), strings(&["arm"]));
+        assert_eq!(name_after("a connecting rod and a piston", 1), strings(&["connecting", "rod"]));
+    }
+
+    #[test]
+    fn names_end_at_punctuation_and_stop_words() {
+        assert_eq!(name_after("a rigid housing; and a lid", 1), strings(&["rigid", "housing"]));
+        assert_eq!(name_after("a flange extending from the base", 1), strings(&["flange"]));
+        assert_eq!(name_after("a spring 108 biasing the lid", 1), strings(&["spring"]));
+        assert!(name_after("the same", 0).is_empty());
+        assert!(name_after("a housing", 2).is_empty());
+    }
+
+    #[test]
+    fn names_are_capped_in_length() {
+        assert_eq!(name_after("a first upper outer support arm", 1).len(), MAX_NAME_WORDS);
+    }
+}
@@ -0,0 +1,260 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::crosscheck::canonical_figure;
+
+/// A FIG. reference, or a list of them after "FIGS.", e.g. "FIGS. 1C-1D" or "FIGS. 2A and 2B".
+/// As with FIG_PATTERN, a letter split into its own run ("FIG. 1 A") still belongs to the figure.
+static FIG_LIST_PATTERN: &str = r"(?i)\b(FIGS?|FIGURES?)\.?\s*([0-9]+(?:\s*[A-Za-z])?)\b((?:\s*(?:,|-|–|\band\b|\bor\b|\bto\b|\bthrough\b)\s*(?:and\s+)?[0-9]+(?:\s*[A-Za-z])?\b)*)";
+
+/// Verbs that end the leading clause of a figure description, e.g. "FIG. 1 is a ..."
+static DESCRIPTION_VERBS: &[&str] = &[
+    "is", "are", "shows", "show", "illustrates", "illustrate", "depicts", "depict", "represents", "represent",
+];
+
+/// Words whose trailing period does not end a sentence, e.g. "FIG. 4"
+static ABBREVIATIONS: &[&str] = &["fig", "figs", "no", "nos", "e.g", "i.e", "approx", "ref"];
+
+/// Largest span a figure range is expanded over, e.g. "FIGS. 3-8"
+const MAX_FIGURE_RANGE: u32 = 50;
+
+/// A figure declared in the BRIEF DESCRIPTION OF THE DRAWINGS
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FigureDescription {
+    pub figure: String,       // Canonical label, e.g. "FIG. 1A"
+    pub description: String,  // The paragraph that declares it
+}
+
+/// Split a figure label into its number and letter, e.g. "7B" -> (7, "B")
+fn split_figure(label: &str) -> Option<(u32, String)> {
+    let end = label.find(|c: char| !c.is_ascii_digit()).unwrap_or(label.len());
+    let number = label[..end].parse().ok()?;
+    Some((number, label[end..].to_uppercase()))
+}
+
+/// Expand a figure range such as "3-5" or "7A-7C". A range across lettered figures such as
+/// "7A-8" covers whichever `known` figures fall between its ends; otherwise just the ends are kept.
+fn expand_figure_range(start: &str, end: &str, known: &[String]) -> Vec<String> {
+    let endpoints = vec![start.to_string(), end.to_string()];
+    let (Some(first), Some(last)) = (split_figure(start), split_figure(end)) else {
+        return endpoints;
+    };
+
+    if first.1.is_empty() && last.1.is_empty() && last.0 > first.0 && last.0 - first.0 <= MAX_FIGURE_RANGE {
+        return (first.0..=last.0).map(|n| n.to_string()).collect();
+    }
+
+    // Lettered views of one figure, e.g. 7A-7C
+    let mut letters = (first.1.chars(), last.1.chars());
+    if let ((Some(a), None), (Some(b), None)) = ((letters.0.next(), letters.0.next()), (letters.1.next(), letters.1.next())) {
+        if first.0 == last.0 && b > a {
+            return (a..=b).map(|c| format!("{}{}", first.0, c)).collect();
+        }
+    }
+
+    let within: Vec<String> = known.iter()
+        .filter_map(|figure| split_figure(figure.trim_start_matches("FIG. ")))
+        .filter(|figure| *figure >= first && *figure <= last)
+        .map(|(number, letter)| format!("{}{}", number, letter))
+        .collect();
+    if within.is_empty() { endpoints } else { within }
+}
+
+/// Every figure referenced in a piece of text, in order of appearance and without repeats.
+/// Lists and ranges after "FIGS." are expanded, so "FIGS. 1C-1D" yields FIG. 1C and FIG. 1D;
+/// `known` figures let a range like "FIGS. 7A-8" pick up 7B through 7F as well.
+pub fn figure_references(text: &str, known: &[String]) -> Vec<String> {
+    let fig_list = Regex::new(FIG_LIST_PATTERN).unwrap();
+    let item = Regex::new(r"[0-9]+(?:\s*[A-Za-z]\b)?").unwrap();
+
+    let mut figures = Vec::new();
+    for cap in fig_list.captures_iter(text) {
+        let keyword = cap.get(1).unwrap().as_str();
+        let mut labels = vec![cap.get(2).unwrap().as_str().replace(char::is_whitespace, "")];
+
+        // Only the plural keyword introduces a list; "FIG. 1 and 102" is one figure and a numeral
+        if keyword.to_lowercase().ends_with('s') {
+            let tail = cap.get(3).map_or("", |m| m.as_str());
+            let mut previous_end = 0;
+            for m in item.find_iter(tail) {
+                let separator = tail[previous_end..m.start()].to_lowercase();
+                let is_range = separator.contains('-') || separator.contains('–')
+                    || separator.contains("to") || separator.contains("through");
+                let label = m.as_str().replace(char::is_whitespace, "");
+                match labels.pop() {
+                    Some(start) if is_range => labels.extend(expand_figure_range(&start, &label, known)),
+                    Some(start) => labels.extend([start, label]),
+                    None => labels.push(label),
+                }
+                previous_end = m.end();
+            }
+        }
+
+        for label in labels {
+            if let Some((number, letter)) = split_figure(&label) {
+                let figure = canonical_figure(&number.to_string(), Some(&letter));
+                if !figures.contains(&figure) {
+                    figures.push(figure);
+                }
+            }
+        }
+    }
+    figures
+}
+
+/// Whether a paragraph looks like a section heading, e.g. "DETAILED DESCRIPTION"
+pub fn is_heading(paragraph: &str) -> bool {
+    let trimmed = paragraph.trim();
+    trimmed.len() <= 80
+        && !trimmed.to_uppercase().starts_with("FIG")
+        && trimmed.chars().any(|c| c.is_alphabetic())
+        && !trimmed.chars().any(|c| c.is_lowercase())
+}
+
+/// Whether a heading starts the figure list, e.g. "BRIEF DESCRIPTION OF THE DRAWINGS"
+fn is_brief_description_heading(paragraph: &str) -> bool {
+    let upper = paragraph.trim().to_uppercase();
+    upper.len() <= 80
+        && upper.contains("BRIEF DESCRIPTION")
+        && (upper.contains("DRAWING") || upper.contains("FIGURE"))
+}
+
+/// Whether a heading starts the detailed description
+fn is_detailed_description_heading(paragraph: &str) -> bool {
+    let upper = paragraph.trim().to_uppercase();
+    upper.len() <= 80 && upper.contains("DETAILED DESCRIPTION")
+}
+
+/// Whether a heading starts the claims or abstract, which end the description
+fn is_closing_heading(paragraph: &str) -> bool {
+    let upper = paragraph.trim().to_uppercase();
+    upper.len() <= 80 && (upper.contains("CLAIM") || upper.contains("ABSTRACT"))
+}
+
+/// Paragraphs between a heading matching `starts` and the next one matching `ends`
+fn section(paragraphs: &[String], starts: impl Fn(&str) -> bool, ends: impl Fn(&str) -> bool) -> Option<Range<usize>> {
+    let start = paragraphs.iter().position(|p| starts(p))? + 1;
+    let end = paragraphs[start..].iter()
+        .position(|p| ends(p))
+        .map_or(paragraphs.len(), |offset| start + offset);
+    Some(start..end)
+}
+
+/// Strip a leading paragraph number such as "[0012]" from a paragraph
+fn strip_paragraph_number(paragraph: &str) -> &str {
+    let trimmed = paragraph.trim_start();
+    match trimmed.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        Some((number, rest)) if number.chars().all(|c| c.is_ascii_digit()) => rest.trim_start(),
+        _ => trimmed,
+    }
+}
+
+/// Cut a figure description at its verb, e.g. "FIGS. 2A and 2B are ..." -> "FIGS. 2A and 2B"
+fn leading_clause(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let verb = words.iter()
+        .position(|w| DESCRIPTION_VERBS.contains(&w.trim_end_matches([',', ';', ':']).to_lowercase().as_str()))
+        .unwrap_or(words.len());
+    words[..verb].join(" ")
+}
+
+/// The first sentence of a paragraph, ignoring periods in abbreviations like "FIG."
+fn first_sentence(text: &str) -> &str {
+    let mut word_start = 0;
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            word_start = i + c.len_utf8();
+        } else if c == '.' && text[i + 1..].starts_with(char::is_whitespace) {
+            let word = text[word_start..i].trim_start_matches(['(', '"']).to_lowercase();
+            if !ABBREVIATIONS.contains(&word.as_str()) {
+                return &text[..=i];
+            }
+        }
+    }
+    text
+}
+
+/// Extract the figures declared in the BRIEF DESCRIPTION OF THE DRAWINGS section.
+/// Only figures in the leading clause count, so "FIG. 2 is a section of FIG. 1"
+/// declares FIG. 2 alone. Returns nothing when the section is missing.
+pub fn brief_description_of_drawings(paragraphs: &[String]) -> Vec<FigureDescription> {
+    let Some(range) = section(paragraphs, is_brief_description_heading, is_heading) else {
+        println!("[DEBUG] No BRIEF DESCRIPTION OF THE DRAWINGS section found");
+        return Vec::new();
+    };
+
+    let mut declared: Vec<FigureDescription> = Vec::new();
+    for paragraph in &paragraphs[range] {
+        let text = strip_paragraph_number(paragraph);
+        if !text.to_uppercase().starts_with("FIG") {
+            continue;
+        }
+
+        for figure in figure_references(&leading_clause(text), &[]) {
+            if !declared.iter().any(|d| d.figure == figure) {
+                declared.push(FigureDescription { figure, description: text.to_string() });
+            }
+        }
+    }
+
+    println!("[DEBUG] Figures declared in the brief description: {}", declared.len());
+    declared
+}
+
+/// Figures referenced in the detailed description. Without a DETAILED DESCRIPTION
+/// heading, everything after the brief description up to the claims is used.
+pub fn detailed_description_figures(paragraphs: &[String], declared: &[FigureDescription]) -> Vec<String> {
+    let known: Vec<String> = declared.iter().map(|d| d.figure.clone()).collect();
+    let range = section(paragraphs, is_detailed_description_heading, is_closing_heading)
+        .or_else(|| {
+            let brief = section(paragraphs, is_brief_description_heading, is_heading)?;
+            let end = paragraphs[brief.end..].iter()
+                .position(|p| is_closing_heading(p))
+                .map_or(paragraphs.len(), |offset| brief.end + offset);
+            Some(brief.end..end)
+        })
+        .unwrap_or(0..0);
+
+    let mut figures: Vec<String> = Vec::new();
+    for paragraph in &paragraphs[range] {
+        for figure in figure_references(paragraph, &known) {
+            if !figures.contains(&figure) {
+                figures.push(figure);
+            }
+        }
+    }
+    figures
+}
+
+/// The figure context of each paragraph, e.g. FIG. 4 for the paragraphs after
+/// "Referring now to FIG. 4, ...". A paragraph whose first sentence names figures sets
+/// the context, and later paragraphs inherit it until the next heading. When the sentence
+/// opens with the figures ("FIG. 4 shows ..."), only those before the verb count.
+/// Paragraphs in the brief description of the drawings have no context.
+pub fn figure_contexts(paragraphs: &[String], declared: &[FigureDescription]) -> Vec<Vec<String>> {
+    let known: Vec<String> = declared.iter().map(|d| d.figure.clone()).collect();
+    let brief = section(paragraphs, is_brief_description_heading, is_heading).unwrap_or(0..0);
+
+    let mut contexts = Vec::with_capacity(paragraphs.len());
+    let mut current: Vec<String> = Vec::new();
+    for (index, paragraph) in paragraphs.iter().enumerate() {
+        if brief.contains(&index) || is_heading(paragraph) {
+            current.clear();
+            contexts.push(Vec::new());
+            continue;
+        }
+
+        let sentence = first_sentence(strip_paragraph_number(paragraph));
+        let figures = if sentence.to_uppercase().starts_with("FIG") {
+            figure_references(&leading_clause(sentence), &known)
+        } else {
+            figure_references(sentence, &known)
+        };
+        if !figures.is_empty() {
+            current = figures;
+        }
+        contexts.push(current.clone());
+    }
+    contexts
+}
@@ -0,0 +1,172 @@
+/// Characters OCR commonly reads in place of a digit, as (misread, digit)
+const CONFUSIONS: [(char, char); 8] = [
+    ('o', '0'),
+    ('i', '1'),
+    ('l', '1'),
+    ('|', '1'),
+    ('s', '5'),
+    ('b', '8'),
+    ('z', '2'),
+    ('g', '9'),
+];
+
+/// Cost of reading one character as a character it is easily confused with
+const CONFUSION_COST: f32 = 0.25;
+
+/// Cost of a stray stroke in the reading, e.g. a leader line or slash read as "1"
+const STRAY_STROKE_COST: f32 = 0.5;
+
+/// Cost of any other substituted, extra or missing character
+const EDIT_COST: f32 = 1.0;
+
+/// Most a single numeral within a reading may differ from the spec numeral it is matched to
+const MAX_PIECE_COST: f32 = 1.0;
+
+/// Most a whole reading may differ from the numerals it is matched to
+const MAX_READING_COST: f32 = 1.5;
+
+/// The spec numerals a drawing reading most likely stands for
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct FuzzyMatch {
+    pub numerals: Vec<String>,  // One numeral, or several run together as in "1011402" for "101" and "102"
+    pub score: f32,             // 1 for an exact reading, lower the more edits the match needed
+}
+
+/// Whether OCR readily reads one character as the other
+pub fn confusable(a: char, b: char) -> bool {
+    CONFUSIONS.iter().any(|&(misread, digit)| (a, b) == (misread, digit) || (a, b) == (digit, misread))
+}
+
+/// Cost of dropping a character the reading has but the numeral does not
+fn extra_character_cost(c: char) -> f32 {
+    if matches!(c, '1' | 'i' | 'l' | '|' | '/' | '-') {
+        STRAY_STROKE_COST
+    } else {
+        EDIT_COST
+    }
+}
+
+/// Edit distance from a reading to a numeral, with confusable characters and stray strokes costing less
+pub fn confusion_distance(reading: &[char], numeral: &[char]) -> f32 {
+    let mut previous: Vec<f32> = (0..=numeral.len()).map(|j| j as f32 * EDIT_COST).collect();
+    for &r in reading {
+        let mut current = vec![previous[0] + extra_character_cost(r)];
+        for (j, &n) in numeral.iter().enumerate() {
+            let substitution = if r == n {
+                0.0
+            } else if confusable(r, n) {
+                CONFUSION_COST
+            } else {
+                EDIT_COST
+            };
+            let cost = (previous[j] + substitution)
+                .min(previous[j + 1] + extra_character_cost(r))
+                .min(current[j] + EDIT_COST);
+            current.push(cost);
+        }
+        previous = current;
+    }
+    previous[numeral.len()]
+}
+
+/// Find the spec numerals a drawing reading most likely stands for. The reading is split into
+/// pieces wherever that explains it better, so merged labels are matched to each of their
+/// numerals, and stray strokes between them are skipped. Returns None when nothing is close.
+pub fn best_match(reading: &str, numerals: &[String]) -> Option<FuzzyMatch> {
+    let reading: Vec<char> = reading.to_lowercase().chars().collect();
+    let numerals: Vec<(&String, Vec<char>)> = numerals.iter()
+        .map(|numeral| (numeral, numeral.to_lowercase().chars().collect()))
+        .collect();
+    let longest = numerals.iter().map(|(_, chars)| chars.len()).max()?;
+
+    // Cheapest explanation of each prefix of the reading, as (cost, numerals)
+    let mut best: Vec<Option<(f32, Vec<&String>)>> = vec![None; reading.len() + 1];
+    best[0] = Some((0.0, Vec::new()));
+    for start in 0..reading.len() {
+        let Some((cost, matched)) = best[start].clone() else { continue };
+
+        // Skip a character that belongs to no numeral
+        let mut candidates = vec![(start + 1, cost + extra_character_cost(reading[start]), matched.clone())];
+
+        // Or read the next few characters as one numeral
+        for end in (start + 1)..=(start + longest + 1).min(reading.len()) {
+            let piece = &reading[start..end];
+            let closest = numerals.iter()
+                .map(|(numeral, chars)| (*numeral, confusion_distance(piece, chars)))
+                .filter(|(_, distance)| *distance <= MAX_PIECE_COST)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((numeral, distance)) = closest {
+                let mut with_numeral = matched.clone();
+                with_numeral.push(numeral);
+                candidates.push((end, cost + distance, with_numeral));
+            }
+        }
+
+        // Keep the cheaper explanation, and the one with fewer numerals on a tie
+        for (end, cost, matched) in candidates {
+            let better = best[end].as_ref().is_none_or(|(best_cost, best_matched)| {
+                cost < *best_cost || (cost == *best_cost && matched.len() < best_matched.len())
+            });
+            if better {
+                best[end] = Some((cost, matched));
+            }
+        }
+    }
+
+    let (cost, matched) = best[reading.len()].take()?;
+    if matched.is_empty() || cost > MAX_READING_COST {
+        return None;
+    }
+    Some(FuzzyMatch {
+        numerals: matched.into_iter().cloned().collect(),
+        score: (1.0 - cost / reading.len() as f32).max(0.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numerals(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn matched(reading: &str) -> Option<Vec<String>> {
+        let spec = numerals(&["101", "102", "104", "108", "110", "160-1", "160-2", "170"]);
+        best_match(reading, &spec).map(|m| m.numerals)
+    }
+
+    #[test]
+    fn exact_readings_score_one() {
+        let spec = numerals(&["102", "104"]);
+        assert_eq!(best_match("104", &spec), Some(FuzzyMatch { numerals: numerals(&["104"]), score: 1.0 }));
+    }
+
+    #[test]
+    fn confused_characters_are_cheap() {
+        assert_eq!(matched("1O8"), Some(numerals(&["108"])));
+        assert_eq!(matched("l1O"), Some(numerals(&["110"])));
+        assert!(best_match("1O8", &numerals(&["108"])).unwrap().score > 0.9);
+        assert_eq!(confusion_distance(&['s'], &['5']), CONFUSION_COST);
+        assert_eq!(confusion_distance(&['7'], &['5']), EDIT_COST);
+    }
+
+    #[test]
+    fn merged_readings_match_each_numeral() {
+        assert_eq!(matched("1011402"), Some(numerals(&["101", "102"])));
+        assert_eq!(matched("102104"), Some(numerals(&["102", "104"])));
+    }
+
+    #[test]
+    fn stray_strokes_are_skipped() {
+        assert_eq!(matched("1160-1"), Some(numerals(&["160-1"])));
+        assert_eq!(matched("/170"), Some(numerals(&["170"])));
+    }
+
+    #[test]
+    fn distant_readings_match_nothing() {
+        assert_eq!(matched("9999"), None);
+        assert_eq!(matched(""), None);
+        assert_eq!(best_match("102", &[]), None);
+    }
+}
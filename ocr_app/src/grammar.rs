@@ -0,0 +1,282 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+/// Most digits a numeral or its sub-number may have; longer runs are not reference numerals
+pub const MAX_DIGITS: usize = 8;
+
+/// Characters that already mean something else in a label and cannot separate a sub-number
+const RESERVED_SEPARATORS: &str = "()'\"";
+
+/// Which tokens count as reference numerals. Loaded from a TOML profile per client
+/// or jurisdiction and shared by the OCR and DOCX extraction code.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LabelGrammar {
+    #[serde(deserialize_with = "numeral_digits")]
+    pub min_digits: usize,              // Fewest digits in a numeral, e.g. 2 for "10"
+    #[serde(deserialize_with = "numeral_digits")]
+    pub max_digits: usize,              // Most digits in a numeral, e.g. 4 for "1000"
+    pub letter_suffix: bool,            // Numerals like "102a"
+    #[serde(deserialize_with = "separators")]
+    pub sub_number_separators: String,  // Separators before a sub-number, e.g. "-./" for "130-1", "12.3", "200/1"
+    #[serde(deserialize_with = "sub_digits")]
+    pub max_sub_digits: usize,          // Most digits after a separator
+    pub parenthesized_suffix: bool,     // Numerals like "130(1)" or "130(n)"
+    pub primes: bool,                   // Numerals like "102'" or "102''"
+    pub prefixes: Vec<String>,          // Allowed prefixes, e.g. "S" for step labels like "S100"
+}
+
+impl Default for LabelGrammar {
+    fn default() -> Self {
+        Self {
+            min_digits: 2,
+            max_digits: 4,
+            letter_suffix: true,
+            sub_number_separators: "-".to_string(),
+            max_sub_digits: 2,
+            parenthesized_suffix: true,
+            primes: true,
+            prefixes: Vec::new(),
+        }
+    }
+}
+
+/// Check a digit count for a numeral, from 1 to MAX_DIGITS
+fn check_digits(name: &str, digits: usize) -> Result<()> {
+    if digits == 0 || digits > MAX_DIGITS {
+        bail!("{} must be between 1 and {}, got {}", name, MAX_DIGITS, digits);
+    }
+    Ok(())
+}
+
+/// Check that every sub-number separator is punctuation with no other meaning in a label
+fn check_separators(separators: &str) -> Result<()> {
+    if let Some(c) = separators.chars().find(|&c| !c.is_ascii_punctuation() || RESERVED_SEPARATORS.contains(c)) {
+        bail!("Sub-number separator {:?} is not allowed; use punctuation such as - . /", c);
+    }
+    Ok(())
+}
+
+fn numeral_digits<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let digits = usize::deserialize(deserializer)?;
+    check_digits("Numeral digits", digits).map_err(serde::de::Error::custom)?;
+    Ok(digits)
+}
+
+fn sub_digits<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let digits = usize::deserialize(deserializer)?;
+    if digits > MAX_DIGITS {
+        return Err(serde::de::Error::custom(format!("max_sub_digits must be at most {}, got {}", MAX_DIGITS, digits)));
+    }
+    Ok(digits)
+}
+
+fn separators<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let separators = String::deserialize(deserializer)?;
+    check_separators(&separators).map_err(serde::de::Error::custom)?;
+    Ok(separators)
+}
+
+/// Whether a character can mark a primed numeral, e.g. 102' or 102″
+pub fn is_prime_mark(c: char) -> bool {
+    matches!(c, '\'' | '"' | '\u{2019}' | '\u{201D}' | '\u{2032}' | '\u{2033}')
+}
+
+impl LabelGrammar {
+    /// Grammar equivalent to the web UI's original five label flags
+    pub fn from_flags(allow_2: bool, allow_3: bool, allow_4: bool, allow_letters: bool, allow_hyphen: bool) -> Self {
+        let enabled: Vec<usize> = [(2, allow_2), (3, allow_3), (4, allow_4)]
+            .iter()
+            .filter(|(_, allowed)| *allowed)
+            .map(|(digits, _)| *digits)
+            .collect();
+
+        Self {
+            min_digits: enabled.first().copied().unwrap_or(2),
+            max_digits: enabled.last().copied().unwrap_or(4),
+            letter_suffix: allow_letters,
+            sub_number_separators: if allow_hyphen { "-".to_string() } else { String::new() },
+            ..Self::default()
+        }
+    }
+
+    /// Load a grammar from a TOML profile; missing keys fall back to the defaults
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read label profile {}", path.as_ref().display()))?;
+        let grammar: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse label profile {}", path.as_ref().display()))?;
+        grammar.validate()
+            .with_context(|| format!("Invalid label profile {}", path.as_ref().display()))?;
+        Ok(grammar)
+    }
+
+    /// Check the limits a grammar must stay within to build its regex
+    pub fn validate(&self) -> Result<()> {
+        check_digits("min_digits", self.min_digits)?;
+        check_digits("max_digits", self.max_digits)?;
+        if self.max_sub_digits > MAX_DIGITS {
+            bail!("max_sub_digits must be at most {}, got {}", MAX_DIGITS, self.max_sub_digits);
+        }
+        check_separators(&self.sub_number_separators)
+    }
+
+    /// Build a regex matching exactly one valid label
+    pub fn regex(&self) -> Result<Regex> {
+        self.validate()?;
+        let mut pattern = String::from("^");
+
+        if !self.prefixes.is_empty() {
+            // OCR and text-layer words are lowercased, so "S100" arrives as "s100"
+            let prefixes: Vec<String> = self.prefixes.iter().map(|p| regex::escape(p)).collect();
+            pattern.push_str(&format!("(?i:{})?", prefixes.join("|")));
+        }
+
+        pattern.push_str(&format!(r"\d{{{},{}}}", self.min_digits, self.max_digits.max(self.min_digits)));
+        if self.letter_suffix {
+            pattern.push_str("[a-zA-Z]?");
+        }
+
+        if !self.sub_number_separators.is_empty() && self.max_sub_digits > 0 {
+            let separators: String = self.sub_number_separators.chars().map(|c| regex::escape(&c.to_string())).collect();
+            pattern.push_str(&format!(r"(?:[{}]\d{{1,{}}}[a-zA-Z]?)?", separators, self.max_sub_digits));
+        }
+
+        if self.parenthesized_suffix {
+            pattern.push_str(r"(?:\([0-9a-zA-Z]{1,3}\))?");
+        }
+
+        // Any label may carry a prime or double prime, e.g. 102' or 102''
+        if self.primes {
+            pattern.push_str("(?:'{1,2})?");
+        }
+
+        pattern.push('$');
+        Regex::new(&pattern).context("Failed to build the label grammar regex")
+    }
+
+    /// Clean a token by removing everything the grammar cannot use.
+    /// Prime marks after a numeral are kept in canonical ASCII form, so "102′" and
+    /// "102'" both become "102'" and "102″" becomes "102''".
+    pub fn clean_token(&self, raw: &str) -> String {
+        // In a quoted token like "102" the closing mark is a quote, not a prime
+        let quoted = raw.starts_with(['\'', '"', '\u{2018}', '\u{201C}']);
+
+        let mut cleaned = String::new();
+        for c in raw.chars() {
+            let kept = c.is_alphanumeric()
+                || c == '-'
+                || self.sub_number_separators.contains(c)
+                || (self.parenthesized_suffix && (c == '(' || c == ')'));
+            if kept {
+                cleaned.push(c);
+            } else if self.primes && is_prime_mark(c) && !quoted && !cleaned.is_empty() {
+                match c {
+                    '"' | '\u{201D}' | '\u{2033}' => cleaned.push_str("''"),
+                    _ => cleaned.push('\''),
+                }
+            }
+        }
+
+        // Drop separators and parentheses that only wrap the token, e.g. "(104)." -> "104"
+        let is_separator = |c: char| c == '-' || self.sub_number_separators.contains(c);
+        let mut cleaned = cleaned.trim_start_matches(|c: char| is_separator(c) || c == '(').to_string();
+        loop {
+            let trimmed = cleaned.trim_end_matches(is_separator);
+            let trimmed = if trimmed.ends_with(')') && !trimmed.contains('(') {
+                trimmed.trim_end_matches(')')
+            } else {
+                trimmed
+            };
+            if trimmed.len() == cleaned.len() {
+                break;
+            }
+            cleaned = trimmed.to_string();
+        }
+        cleaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_grammar_matches_labels() {
+        let regex = LabelGrammar::default().regex().unwrap();
+        for label in ["10", "102", "1000", "102a", "130-1", "130(1)", "130(n)", "102'", "102''"] {
+            assert!(regex.is_match(label), "{}", label);
+        }
+        for token in ["1", "10000", "130-123", "130.1", "S100", "ab"] {
+            assert!(!regex.is_match(token), "{}", token);
+        }
+    }
+
+    #[test]
+    fn separators_are_escaped() {
+        let grammar = LabelGrammar { sub_number_separators: "-./^[]\\".to_string(), ..LabelGrammar::default() };
+        let regex = grammar.regex().unwrap();
+        for label in ["130-1", "12.3", "200/1", "12^3", "12[3", "12]3", "12\\3"] {
+            assert!(regex.is_match(label), "{}", label);
+        }
+        assert!(!regex.is_match("12x3"));
+    }
+
+    #[test]
+    fn invalid_grammars_are_errors() {
+        for separators in ["x", "u", "b", "(", "'", " "] {
+            let grammar = LabelGrammar { sub_number_separators: separators.to_string(), ..LabelGrammar::default() };
+            assert!(grammar.regex().is_err(), "{:?}", separators);
+        }
+        let grammar = LabelGrammar { max_digits: 100_000, ..LabelGrammar::default() };
+        assert!(grammar.regex().is_err());
+        let grammar = LabelGrammar { min_digits: 0, ..LabelGrammar::default() };
+        assert!(grammar.regex().is_err());
+        let grammar = LabelGrammar { max_sub_digits: MAX_DIGITS + 1, ..LabelGrammar::default() };
+        assert!(grammar.regex().is_err());
+    }
+
+    #[test]
+    fn deserialization_rejects_invalid_grammars() {
+        assert!(serde_json::from_str::<LabelGrammar>(r#"{"sub_number_separators": "x"}"#).is_err());
+        assert!(serde_json::from_str::<LabelGrammar>(r#"{"max_digits": 100000}"#).is_err());
+        assert!(serde_json::from_str::<LabelGrammar>(r#"{"max_sub_digits": 100000}"#).is_err());
+        assert!(toml::from_str::<LabelGrammar>("min_digits = 0").is_err());
+        let grammar: LabelGrammar = serde_json::from_str(r#"{"sub_number_separators": "-./", "max_digits": 3}"#).unwrap();
+        assert_eq!(grammar.max_digits, 3);
+        assert_eq!(grammar.min_digits, 2);
+    }
+
+    #[test]
+    fn profiles_load() {
+        for profile in ["profiles/default.toml", "profiles/sub_numbers.toml"] {
+            let grammar = LabelGrammar::from_toml_file(profile).unwrap();
+            assert!(grammar.regex().is_ok(), "{}", profile);
+        }
+    }
+
+    #[test]
+    fn prefixes_match_in_either_case() {
+        let grammar = LabelGrammar::from_toml_file("profiles/sub_numbers.toml").unwrap();
+        let regex = grammar.regex().unwrap();
+        for label in ["S100", "s100", "100", "12.3"] {
+            assert!(regex.is_match(label), "{}", label);
+        }
+        assert!(!regex.is_match("T100"));
+        assert_eq!(crate::normalize_number(&crate::normalize_text("S100"), &crate::orientation::LabelMatcher::new(&grammar).unwrap()), "s100");
+    }
+
+    #[test]
+    fn clean_token_drops_wrapping_punctuation() {
+        let grammar = LabelGrammar::default();
+        assert_eq!(grammar.clean_token("(104)."), "104");
+        assert_eq!(grammar.clean_token("130(n),"), "130(n)");
+        assert_eq!(grammar.clean_token("102\u{2032}"), "102'");
+        assert_eq!(grammar.clean_token("102\u{2033}"), "102''");
+        assert_eq!(grammar.clean_token("\"102\""), "102");
+        assert_eq!(grammar.clean_token("-130-1-"), "130-1");
+    }
+}
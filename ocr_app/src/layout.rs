@@ -0,0 +1,103 @@
+use regex::Regex;
+
+use crate::crosscheck::canonical_figure;
+use crate::{OcrResult, FIG_PATTERN};
+
+/// Rounds of centroid refinement after the initial nearest-label assignment
+const REFINEMENT_ROUNDS: usize = 5;
+
+/// A figure on a drawing sheet and the area its results cover
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FigureRegion {
+    pub page: usize,      // 1-based page number
+    pub figure: String,   // Canonical label, e.g. "FIG. 3"
+    pub bbox: [f32; 4],   // Normalized [x1, y1, x2, y2] around the label and every result assigned to it
+}
+
+/// Center of a normalized bounding box
+fn center(bbox: &[f32; 4]) -> (f32, f32) {
+    ((bbox[0] + bbox[2]) / 2.0, (bbox[1] + bbox[3]) / 2.0)
+}
+
+/// Straight-line distance between two points
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Assign every OCR result on one page to a figure on that page.
+/// Each FIG. label seeds a cluster; results join the nearest label, then the clusters are
+/// refined around their centroids so numerals drawn far above a caption still land in its
+/// figure. Results that carry a FIG. label always belong to that figure. Returns one entry
+/// per result, None when the page has no FIG. label at all.
+pub fn assign_figures(ocr_results: &[OcrResult]) -> Vec<Option<String>> {
+    let fig_regex = Regex::new(FIG_PATTERN).unwrap();
+
+    // Find the FIG. labels on the page and where they sit
+    let mut anchors: Vec<(String, (f32, f32))> = Vec::new();
+    let mut labelled: Vec<Option<usize>> = Vec::with_capacity(ocr_results.len());
+    for result in ocr_results {
+        let label = fig_regex.captures(&result.text).map(|cap| {
+            canonical_figure(cap.get(2).unwrap().as_str(), cap.get(3).map(|m| m.as_str()))
+        });
+        labelled.push(label.map(|label| {
+            match anchors.iter().position(|(figure, _)| *figure == label) {
+                Some(index) => index,
+                None => {
+                    anchors.push((label, center(&result.bbox)));
+                    anchors.len() - 1
+                }
+            }
+        }));
+    }
+
+    if anchors.is_empty() {
+        return vec![None; ocr_results.len()];
+    }
+
+    // Start from the labels themselves, then move each cluster to the centroid of its members
+    let mut centroids: Vec<(f32, f32)> = anchors.iter().map(|(_, point)| *point).collect();
+    let mut assignment: Vec<usize> = vec![0; ocr_results.len()];
+    for _ in 0..=REFINEMENT_ROUNDS {
+        for (index, result) in ocr_results.iter().enumerate() {
+            assignment[index] = labelled[index].unwrap_or_else(|| {
+                let point = center(&result.bbox);
+                (0..centroids.len())
+                    .min_by(|&a, &b| distance(point, centroids[a]).total_cmp(&distance(point, centroids[b])))
+                    .unwrap()
+            });
+        }
+
+        let mut sums = vec![(0.0f32, 0.0f32, 0usize); centroids.len()];
+        for (result, &cluster) in ocr_results.iter().zip(&assignment) {
+            let (x, y) = center(&result.bbox);
+            sums[cluster].0 += x;
+            sums[cluster].1 += y;
+            sums[cluster].2 += 1;
+        }
+        for (centroid, (x, y, count)) in centroids.iter_mut().zip(sums) {
+            if count > 0 {
+                *centroid = (x / count as f32, y / count as f32);
+            }
+        }
+    }
+
+    assignment.into_iter().map(|cluster| Some(anchors[cluster].0.clone())).collect()
+}
+
+/// The area each figure on a page covers, given the assignment from `assign_figures`
+pub fn figure_regions(page: usize, ocr_results: &[OcrResult], figures: &[Option<String>]) -> Vec<FigureRegion> {
+    let mut regions: Vec<FigureRegion> = Vec::new();
+    for (result, figure) in ocr_results.iter().zip(figures) {
+        let Some(figure) = figure else { continue };
+        match regions.iter_mut().find(|region| region.figure == *figure) {
+            Some(region) => {
+                region.bbox[0] = region.bbox[0].min(result.bbox[0]);
+                region.bbox[1] = region.bbox[1].min(result.bbox[1]);
+                region.bbox[2] = region.bbox[2].max(result.bbox[2]);
+                region.bbox[3] = region.bbox[3].max(result.bbox[3]);
+            }
+            None => regions.push(FigureRegion { page, figure: figure.clone(), bbox: result.bbox }),
+        }
+    }
+    regions
+}
@@ -3,25 +3,52 @@ extern crate lazy_static;
 use std::path::Path;
 use std::collections::HashSet;
 use anyhow::{Context, Result};
-use image::{ImageBuffer, Rgb, RgbImage};
+use image::{DynamicImage, GrayImage, RgbImage};
 use mupdf::{Colorspace, Device, Document, Matrix, Pixmap};
-use ocrs::{ImageSource, OcrEngine};
+use ocrs::{OcrEngine, TextItem, TextWord};
+use rten_imageproc::{min_area_rect, Point, RotatedRect, Vec2};
 use regex::Regex;
-use docx_rs;
 
+pub mod claims;
+pub mod crosscheck;
+pub mod docx;
+pub mod elements;
+pub mod figures;
+pub mod fuzzy;
+pub mod grammar;
+pub mod layout;
 pub mod models;
+pub mod orientation;
+pub mod preprocess;
+pub mod render;
+pub mod rotated;
+pub mod sheets;
+pub mod spec;
+pub mod text_layer;
+pub mod tiling;
+
+pub use grammar::LabelGrammar;
+pub use preprocess::Binarization;
+pub use render::RenderOptions;
+pub use spec::{ParagraphOrigin, RevisionView, SpecSource};
 
 // Regex pattern for matching FIG/Figure references
 static FIG_PATTERN: &str = r"(?i)\b(FIG\.?|FIGURE\.?|FIG|FIGURE)\s*([0-9]+)\s*([A-Za-z])?\b";
 
-/// Convert a PDF page to an RGB image
-pub fn pdf_page_to_image(doc: &Document, page_num: i32, dpi: f32) -> Result<RgbImage> {
+lazy_static::lazy_static! {
+    static ref FIG_REGEX: Regex = Regex::new(FIG_PATTERN).unwrap();
+    static ref FIG_KEYWORD_REGEX: Regex = Regex::new(r"(?i)^(FIG|FIGURE)S?\.?$").unwrap();
+}
+
+/// Convert a PDF page to an RGB image, rendered and binarized as the options ask
+pub fn pdf_page_to_image(doc: &Document, page_num: i32, options: &RenderOptions) -> Result<RgbImage> {
     let page = doc.load_page(page_num)
         .context("Failed to load PDF page")?;
     
     // Calculate dimensions based on DPI
     let bounds = page.bounds()
         .context("Failed to get page bounds")?;
+    let dpi = options.dpi_for(bounds.x1 - bounds.x0, bounds.y1 - bounds.y0);
     let scale = dpi / 72.0; // Convert from PDF points (72 DPI) to target DPI
     let width = ((bounds.x1 - bounds.x0) * scale) as i32;
     let height = ((bounds.y1 - bounds.y0) * scale) as i32;
@@ -49,197 +76,216 @@ pub fn pdf_page_to_image(doc: &Document, page_num: i32, dpi: f32) -> Result<RgbI
     page.run(&device, &transform)
         .context("Failed to render page to pixmap")?;
 
-    // Convert pixmap to image::RgbImage
+    // Copy the grayscale samples into an image, treating anything missing as white paper
     let samples = pixmap.samples();
-    
-    let mut img = ImageBuffer::new(width as u32, height as u32);
-    
-    // Convert grayscale to black and white with a fixed threshold
-    for y in 0..height {
-        for x in 0..width {
-            let idx = (y * width + x) as usize;
-            if idx < samples.len() {
-                let gray = samples[idx];
-                // Use a lower threshold to catch lighter text
-                let value = if gray < 160 { 0 } else { 255 };
-                let pixel = Rgb([value, value, value]);
-                img.put_pixel(x as u32, y as u32, pixel);
-            }
-        }
-    }
+    let gray = GrayImage::from_fn(width as u32, height as u32, |x, y| {
+        let idx = (y * width as u32 + x) as usize;
+        image::Luma([samples.get(idx).copied().unwrap_or(255)])
+    });
 
-    Ok(img)
+    // Binarize, then hand the OCR engine the RGB image it expects
+    let binarized = preprocess::binarize(&gray, options.binarization);
+    Ok(DynamicImage::ImageLuma8(binarized).to_rgb8())
 }
 
 #[derive(serde::Serialize)]
 pub struct OcrResult {
     pub text: String,
     pub bbox: [f32; 4],  // [x1, y1, x2, y2]
+    pub quad: [[f32; 2]; 4],  // Normalized corners of the rotated word box, clockwise from its top-left
+    pub line: usize,          // Index of the line the word was read in, unique within the page
+    pub line_text: String,    // Normalized text of that whole line
+    pub confidence: f32,      // How sure detection is that the box holds text, from 0 to 1; see orientation::word_confidence
+    pub label_fit: f32,       // How well the reading fits a label, FIG. reference or word, from 0 to 1; not a recognition confidence
+    pub source: ResultSource,
+}
+
+/// OCR results below this confidence are uncertain, both in the viewer and in the cross-check
+pub const LOW_CONFIDENCE: f32 = 0.6;
+
+/// Where the text of a result came from
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultSource {
+    /// Recognized from the rendered page
+    Ocr,
+    /// Taken from text objects in the PDF, so exact as written
+    TextLayer,
+}
+
+/// An element introduced in the specification, e.g. "housing 102"
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SpecElement {
+    pub name: String,     // Normalized element name, e.g. "first support arm"
+    pub numeral: String,  // Normalized numeral, e.g. "102"
+    pub text: String,     // The match as written, e.g. "housings 102"
+    pub expanded_from: Option<String>,  // The list or range this numeral was expanded from, e.g. "110a-110c"
+    pub range_interior: bool,  // Strictly inside a numeric range like "120-124", so even numbering may skip it
+    pub figures: Vec<String>,  // Figure context of the paragraph, e.g. ["FIG. 4"] after "Referring now to FIG. 4"
+    pub origin: ParagraphOrigin,  // Part of the document the paragraph is in, e.g. a table cell
 }
 
 #[derive(serde::Serialize)]
 pub struct DocxResult {
-    pub full_matches: Vec<String>,  // Full matches like "word 123"
+    pub full_matches: Vec<String>,  // Full matches like "first support arm 104"
     pub numbers: Vec<String>,      // Just the numbers for comparison
     pub paragraphs: Vec<String>,   // Text content split into paragraphs
+    pub origins: Vec<ParagraphOrigin>,  // Part of the document each paragraph came from, in the same order
+    pub elements: Vec<SpecElement>, // Every name/numeral pairing, including repeats of the same numeral
+    pub figure_descriptions: Vec<figures::FigureDescription>,  // Figures declared in the BRIEF DESCRIPTION OF THE DRAWINGS
+    pub detailed_description_figures: Vec<String>,  // Figures referenced in the detailed description
+    pub claims: Vec<claims::Claim>,  // Numbered claims with their dependencies and element names
+    pub unsupported_claim_terms: Vec<claims::ClaimTermReference>,  // Claim elements the description never gives a numeral
+    pub antecedent_issues: Vec<claims::AntecedentIssue>,  // Claim references with no antecedent basis, or renamed elements
 }
 
 
 
-/// Process a single page and return the extracted text with bounding boxes
-pub fn process_page(engine: &OcrEngine, mut img: RgbImage) -> Result<Vec<OcrResult>> {
-    // Preprocess image to improve OCR
-    for pixel in img.pixels_mut() {
-        // Increase contrast
-        let r = (pixel[0] as f32 * 1.2).min(255.0) as u8;
-        let g = (pixel[1] as f32 * 1.2).min(255.0) as u8;
-        let b = (pixel[2] as f32 * 1.2).min(255.0) as u8;
-        *pixel = Rgb([r, g, b]);
+/// Process a single page and return the extracted text with bounding boxes.
+/// The image is expected to be preprocessed already, e.g. by `pdf_page_to_image`.
+/// Sideways and upside-down sheets are turned upright for OCR, and the boxes are
+/// mapped back so they line up with the page as given. Pages larger than the
+/// tile size in `options` are read in overlapping tiles.
+pub fn process_page(engine: &OcrEngine, img: RgbImage, grammar: &LabelGrammar, options: &RenderOptions) -> Result<Vec<OcrResult>> {
+    if let Some(tile_size) = options.tile_size.filter(|&size| img.width() > size || img.height() > size) {
+        return tiling::process_tiled(engine, &img, grammar, tile_size, options.tile_overlap);
     }
 
-    // Convert image to OCR input format
-    let img_source = ImageSource::from_bytes(img.as_raw(), img.dimensions())
-        .map_err(|e| anyhow::anyhow!("Failed to create image source: {}", e))?;
-    let ocr_input = engine.prepare_input(img_source)
-        .map_err(|e| anyhow::anyhow!("Failed to prepare OCR input: {}", e))?;
-
-    // Detect words and group into lines
-    let word_rects = engine.detect_words(&ocr_input)
-        .map_err(|e| anyhow::anyhow!("Failed to detect words: {}", e))?;
-    let line_rects = engine.find_text_lines(&ocr_input, &word_rects);
-
-    // Recognize text in each line
-    let line_texts = engine.recognize_text(&ocr_input, &line_rects)
-        .map_err(|e| anyhow::anyhow!("Failed to recognize text: {}", e))?;
+    // Recognize the page in whichever orientation reads best
+    let page = orientation::recognize_upright(engine, img, grammar)?;
+    page_results(engine, &page, grammar)
+}
 
-    // Convert results to our format with bounding boxes
+/// Turn a recognized page into one result per word, normalized to the image as given, before rotation
+pub fn page_results(engine: &OcrEngine, page: &orientation::RecognizedPage, grammar: &LabelGrammar) -> Result<Vec<OcrResult>> {
+    let matcher = orientation::LabelMatcher::new(grammar)?;
     let mut ocr_results = Vec::new();
-    let (width, height) = img.dimensions();
-    
-    // Process each line and its words
-    for (line_rects, line_text) in line_rects.iter().zip(line_texts.iter()) {
-        if let Some(text) = line_text {
-            // First normalize the full line text for pattern matching
-            let line_text = text.to_string();
-            println!("[DEBUG] Raw OCR text: {}", line_text);
-            let normalized_line = normalize_text(&line_text);
-            
-            // Process each word in the line
-            for rect in line_rects {
-                // Get the bounding box for this word
-                let corners = rect.corners();
-                let mut min_x = f32::MAX;
-                let mut min_y = f32::MAX;
-                let mut max_x = f32::MIN;
-                let mut max_y = f32::MIN;
-                
-                for corner in corners {
-                    min_x = min_x.min(corner.x);
-                    min_y = min_y.min(corner.y);
-                    max_x = max_x.max(corner.x);
-                    max_y = max_y.max(corner.y);
-                }
+    let (width, height) = page.image.dimensions();
 
-                // Use the normalized line text for pattern matching
-                if normalized_line.chars().any(|c| c.is_ascii_digit()) {
-                    // First look for FIG patterns in the line
-                    let fig_regex = Regex::new(FIG_PATTERN).unwrap();
-                    // Also look for standalone number-letter combinations that might be figure references
-                    let standalone_ref_regex = Regex::new(r"\b([0-9]+[A-Z])\b").unwrap();
-                    let mut results = Vec::new();
-
-                    // Handle explicit FIG patterns first
-                    for cap in fig_regex.captures_iter(&normalized_line) {
-                        if let Some(number) = cap.get(2) {
-                            let num_part = number.as_str();
-                            if num_part.to_uppercase().contains(|c: char| c >= 'A' && c <= 'Z') {
-                                // Get the original FIG prefix from the capture
-                                let fig_prefix = cap.get(1).map(|m| m.as_str()).unwrap_or("FIG.");
-                                results.push(format!("{}{}", fig_prefix, num_part));
-                            }
-                        }
-                    }
-
-                    // If no explicit FIG patterns found, look for standalone number-letter combinations
-                    if results.is_empty() {
-                        for cap in standalone_ref_regex.captures_iter(&normalized_line) {
-                            if let Some(number) = cap.get(1) {
-                                let num_part = number.as_str().to_uppercase();
-                                results.push(format!("FIG.{}", num_part));
-                            }
-                        }
-                    }
+    // Process each line and its words
+    for (line_index, line) in page.line_texts.iter().enumerate() {
+        let Some(line) = line else { continue };
+        let line_text = line.to_string();
+        println!("[DEBUG] Raw OCR text: {}", line_text);
+        let normalized_line = normalize_text(&line_text);
+
+        let words: Vec<TextWord> = line.words().collect();
+        let mut i = 0;
+        while i < words.len() {
+            // A FIG. keyword and the figure number after it are read as one word
+            let next = words.get(i + 1).map(|word| word.to_string());
+            let joins_next = joins_figure_number(&words[i].to_string(), next.as_deref());
+            let word_group = &words[i..if joins_next { i + 2 } else { i + 1 }];
+            i += word_group.len();
+
+            let raw_word = word_group.iter().map(|word| word.to_string()).collect::<Vec<_>>().join(" ");
+            let normalized_word = normalize_text(&raw_word);
+            if normalized_word.is_empty() {
+                continue;
+            }
 
-                    // If we found FIG patterns, use those
-                    if !results.is_empty() {
-                        // Remove duplicates while preserving order
-                        let mut seen = HashSet::new();
-                        results.retain(|x| seen.insert(x.clone()));
-                        println!("[DEBUG] PDF numbers found: {:?}", results);
-                        results.join(" ")
-                    } else {
-                        // Use default label options for OCR processing
-                        let label_regex = build_label_regex(true, true, true, true, true);
-                        let tokens: Vec<&str> = normalized_line.split_whitespace().collect();
-                        
-                        for raw_token in tokens {
-                            let cleaned = clean_token(raw_token);
-                            if cleaned.is_empty() { continue; }
-                            
-                            if label_regex.is_match(&cleaned) {
-                                results.push(cleaned);
-                            } else {
-                                // Try to split merged tokens
-                                let split_parts = split_merged_label(&cleaned, &label_regex);
-                                results.extend(split_parts);
-                            }
-                        }
-                        
-                        // Remove duplicates while preserving order
-                        let mut seen = HashSet::new();
-                        results.retain(|x| seen.insert(x.clone()));
-                        println!("[DEBUG] PDF numbers found: {:?}", results);
-                        results.join(" ")
-                    }
-                } else {
-                    normalized_line.clone()
-                };
-                
-                // Log the exact text and its bounding box for debugging
-                println!("[DEBUG] Bounding box text: '{}' at coordinates: [{:.3}, {:.3}, {:.3}, {:.3}]", 
-                    text.to_string().trim(),
-                    min_x / width as f32,
+            // Box the characters of the word
+            let points: Vec<Point<f32>> = word_group.iter()
+                .flat_map(|word| word.chars())
+                .flat_map(|c| c.rect.corners())
+                .map(Point::to_f32)
+                .collect();
+            let Some(rect) = min_area_rect(&points) else { continue };
+            let rect = rect.orient_towards(Vec2::from_yx(-1., 0.));
+            let (min_x, min_y, max_x, max_y) = corner_extent(&rect);
+
+            let confidence = orientation::word_confidence(&page.text_pixels, &rect);
+            let label_fit = orientation::label_fit(&raw_word, &matcher);
+            println!("[DEBUG] Word '{}' at [{:.3}, {:.3}, {:.3}, {:.3}], confidence {:.2}, label fit {:.2}",
+                raw_word,
+                min_x / width as f32,
+                min_y / height as f32,
+                max_x / width as f32,
+                max_y / height as f32,
+                confidence,
+                label_fit
+            );
+
+            ocr_results.push(OcrResult {
+                text: normalized_word,
+                bbox: orientation::unrotate_bbox([
+                    min_x / width as f32,   // Normalize coordinates
                     min_y / height as f32,
                     max_x / width as f32,
-                    max_y / height as f32
-                );
-                
-                // Only create result if we found patterns
-                if !normalized_line.is_empty() {
-                    ocr_results.push(OcrResult {
-                        text: normalized_line.clone(),
-                        bbox: [
-                            min_x / width as f32,   // Normalize coordinates
-                            min_y / height as f32,
-                            max_x / width as f32,
-                            max_y / height as f32,
-                        ],
-                    });
-                }
-            }
+                    max_y / height as f32,
+                ], page.rotation),
+                quad: normalized_quad(&rect, width, height, page.rotation),
+                line: line_index,
+                line_text: normalized_line.clone(),
+                confidence,
+                label_fit,
+                source: ResultSource::Ocr,
+            });
         }
     }
+
+    // Tilted and vertical words are straightened and recognized one at a time, each as its own line
+    for (word_index, rect) in page.rotated_words.iter().enumerate() {
+        let Some(text) = rotated::recognize_word(engine, &page.image, rect, &matcher)? else {
+            continue;
+        };
+        let normalized_word = normalize_text(&text);
+        if normalized_word.is_empty() {
+            continue;
+        }
+
+        let (min_x, min_y, max_x, max_y) = corner_extent(rect);
+        ocr_results.push(OcrResult {
+            text: normalized_word.clone(),
+            bbox: orientation::unrotate_bbox([
+                min_x / width as f32,
+                min_y / height as f32,
+                max_x / width as f32,
+                max_y / height as f32,
+            ], page.rotation),
+            quad: normalized_quad(rect, width, height, page.rotation),
+            line: page.line_texts.len() + word_index,
+            line_text: normalized_word,
+            confidence: orientation::word_confidence(&page.text_pixels, rect),
+            label_fit: orientation::label_fit(&text, &matcher),
+            source: ResultSource::Ocr,
+        });
+    }
+
     Ok(ocr_results)
 }
 
+/// Whether a word is a bare FIG. keyword followed by the figure number, e.g. "FIG." then "1A"
+fn joins_figure_number(word: &str, next: Option<&str>) -> bool {
+    FIG_KEYWORD_REGEX.is_match(word) && next.is_some_and(|next| next.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Axis-aligned extent of a rotated word box as (min_x, min_y, max_x, max_y) in pixels
+fn corner_extent(rect: &RotatedRect) -> (f32, f32, f32, f32) {
+    let mut extent = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for corner in rect.corners() {
+        extent.0 = extent.0.min(corner.x);
+        extent.1 = extent.1.min(corner.y);
+        extent.2 = extent.2.max(corner.x);
+        extent.3 = extent.3.max(corner.y);
+    }
+    extent
+}
+
+/// Corners of a rotated word box, normalized and mapped back onto the page as given
+fn normalized_quad(rect: &RotatedRect, width: u32, height: u32, rotation: u32) -> [[f32; 2]; 4] {
+    rect.corners().map(|corner| {
+        orientation::unrotate_point([corner.x / width as f32, corner.y / height as f32], rotation)
+    })
+}
+
 /// Helper function to normalize text by converting plurals to singular form
 fn normalize_text(text: &str) -> String {
     // Check for FIG references first and preserve them
-    let fig_regex = Regex::new(FIG_PATTERN).unwrap();
-    if let Some(cap) = fig_regex.captures(text) {
+    if let Some(cap) = FIG_REGEX.captures(text) {
         if let Some(number) = cap.get(2) {
             let num_part = number.as_str();
-            if num_part.to_uppercase().contains(|c: char| c >= 'A' && c <= 'Z') {
+            if num_part.to_uppercase().contains(|c: char| c.is_ascii_uppercase()) {
                 return format!("FIG.{}", num_part);
             }
         }
@@ -290,52 +336,15 @@ fn normalize_text(text: &str) -> String {
 ")
 }
 
-/// Build a regex pattern for matching valid label numbers
-fn build_label_regex(allow_2: bool, allow_3: bool, allow_4: bool, allow_letters: bool, allow_hyphen: bool) -> Regex {
-    let mut patterns = Vec::new();
-    
-    if allow_2 {
-        patterns.push(r"\d{2}");
-        if allow_letters {
-            patterns.push(r"\d{2}[a-zA-Z]");
-        }
-    }
-    if allow_3 {
-        patterns.push(r"\d{3}");
-        if allow_letters {
-            patterns.push(r"\d{3}[a-zA-Z]");
-        }
-        if allow_hyphen {
-            patterns.push(r"\d{3}-\d");
-        }
-    }
-    if allow_4 {
-        patterns.push(r"\d{4}");
-        if allow_letters {
-            patterns.push(r"\d{4}[a-zA-Z]");
-        }
-        if allow_hyphen {
-            patterns.push(r"\d{4}-\d");
-        }
-    }
-    
-    Regex::new(&format!(r"({})", patterns.join("|"))).unwrap()
-}
-
-/// Clean a token by removing all non-word and non-hyphen characters
-fn clean_token(raw: &str) -> String {
-    raw.chars()
-        .filter(|c| c.is_alphanumeric() || *c == '-')
-        .collect()
-}
-
-/// Try to split a merged label into valid parts
+/// Try to split a merged (already cleaned) label into valid parts
 fn split_merged_label(token: &str, label_regex: &Regex) -> Vec<String> {
-    let token = clean_token(token);
     let mut results = Vec::new();
     
     // Try all possible splits
-    for i in 2..token.len()-1 {
+    for i in 2..token.len().saturating_sub(1) {
+        if !token.is_char_boundary(i) {
+            continue;
+        }
         let left = &token[..i];
         let right = &token[i..];
         
@@ -349,14 +358,12 @@ fn split_merged_label(token: &str, label_regex: &Regex) -> Vec<String> {
     results
 }
 
-/// Helper function to normalize a number by applying label rules
-pub fn normalize_number(num: &str, allow_2: bool, allow_3: bool, allow_4: bool, allow_letters: bool, allow_hyphen: bool) -> String {
-    // Initialize regex pattern with provided options
-    let label_regex = build_label_regex(allow_2, allow_3, allow_4, allow_letters, allow_hyphen);
-    
+/// Helper function to normalize a number by applying the label rules `matcher` was built from
+pub fn normalize_number(num: &str, matcher: &orientation::LabelMatcher) -> String {
+    let label_regex = &matcher.label_regex;
+
     // Special handling for FIG. variations
-    let fig_regex = Regex::new(FIG_PATTERN).unwrap();
-    if let Some(cap) = fig_regex.captures(num) {
+    if let Some(cap) = matcher.fig_regex.captures(num) {
         if let Some(number) = cap.get(2) {
             let number_str = number.as_str();
             if label_regex.is_match(number_str) {
@@ -371,14 +378,14 @@ pub fn normalize_number(num: &str, allow_2: bool, allow_3: bool, allow_4: bool,
     let mut results = Vec::new();
     
     for raw_token in tokens {
-        let cleaned = clean_token(raw_token);
+        let cleaned = matcher.grammar.clean_token(raw_token);
         if cleaned.is_empty() { continue; }
         
         if label_regex.is_match(&cleaned) {
             results.push(cleaned);
         } else {
             // Try to split merged tokens
-            let split_parts = split_merged_label(&cleaned, &label_regex);
+            let split_parts = split_merged_label(&cleaned, label_regex);
             results.extend(split_parts);
         }
     }
@@ -390,71 +397,41 @@ pub fn normalize_number(num: &str, allow_2: bool, allow_3: bool, allow_4: bool,
     results.join(" ")
 }
 
-pub fn process_docx(_engine: &OcrEngine, docx_path: impl AsRef<Path>, allow_2: bool, allow_3: bool, allow_4: bool, allow_letters: bool, allow_hyphen: bool) -> Result<DocxResult> {
-    // Read DOCX file
-    let docx_content = std::fs::read(docx_path)
-        .context("Failed to read DOCX file")?;
-    let docx = docx_rs::read_docx(&docx_content)
-        .context("Failed to parse DOCX file")?;
-
-    // Extract text and paragraphs from the document
-    println!("[DEBUG] Starting DOCX text extraction");
-    let mut text = String::new();
-    let mut paragraphs = Vec::new();
-    let mut current_paragraph = String::new();
-
-    // Process each paragraph
-    for child in docx.document.children {
-        if let docx_rs::DocumentChild::Paragraph(para) = child {
-            // Extract all text from the paragraph
-            let para_text: String = para.children.iter()
-                .filter_map(|child| {
-                    if let docx_rs::ParagraphChild::Run(run) = child {
-                        Some(run.children.iter().filter_map(|child| {
-                            if let docx_rs::RunChild::Text(text) = child {
-                                Some(text.text.as_str())
-                            } else {
-                                None
-                            }
-                        }).collect::<Vec<_>>().join(""))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(" ");
-            println!("[DEBUG] Paragraph text after joining: {}", para_text);
-            text.push_str(&para_text);
-            text.push('\n');
-            current_paragraph.push_str(&para_text);
-            if !current_paragraph.trim().is_empty() {
-                paragraphs.push(current_paragraph.trim().to_string());
-                current_paragraph.clear();
-            }
-            text.push('\n');
-        }
-    }
-
-    println!("[DEBUG] Final collected text:\n{}", text);
+pub fn process_docx(_engine: &OcrEngine, docx_path: impl AsRef<Path>, grammar: &LabelGrammar) -> Result<DocxResult> {
+    process_spec(&SpecSource::Docx(docx_path.as_ref().to_path_buf()), grammar, RevisionView::Final)
+}
 
-    // Create regex patterns
-    let word_pattern = Regex::new(r"\b(\w+)\s+([^\s]*[0-9][^\s]*)\b")
-        .context("Failed to create word pattern")?;
-    let fig_pattern = Regex::new(FIG_PATTERN)
-        .context("Failed to create FIG pattern")?;
+/// Find the figures and numbered elements of a specification in any of the formats `SpecSource` reads.
+/// `revisions` picks the version of a document with tracked changes to check. Markup is only shown
+/// in the returned paragraphs; its numerals are taken from the final version.
+pub fn process_spec(source: &SpecSource, grammar: &LabelGrammar, revisions: RevisionView) -> Result<DocxResult> {
+    // Extract the paragraphs of the document
+    println!("[DEBUG] Starting {} text extraction", source.format_name());
+    let read = |view| -> Result<(Vec<String>, Vec<ParagraphOrigin>)> {
+        Ok(source.paragraphs(view)?
+            .into_iter()
+            .map(|paragraph| (paragraph.text, paragraph.origin))
+            .unzip())
+    };
+    // Deleted text and the change marks would otherwise turn into numerals
+    let checked = if revisions == RevisionView::Markup { RevisionView::Final } else { revisions };
+    let (paragraphs, origins) = read(checked)?;
+    println!("[DEBUG] Final collected text:\n{}", paragraphs.join("\n\n"));
+
+    // Compile the FIG. and label patterns once for the whole document
+    let matcher = orientation::LabelMatcher::new(grammar)?;
+    let fig_pattern = &matcher.fig_regex;
 
     // Extract full matches and their numbers
     let mut normalized_matches = HashSet::new();
     let mut numbers = HashSet::new();
     let mut full_matches = Vec::new();
-
-    // Keep track of the last meaningful noun for "and NUMBER" cases
-    let mut last_noun = String::new();
-    let mut last_noun_normalized = String::new();
+    let mut spec_elements = Vec::new();
+    let mut seen_in_context = HashSet::new();
 
     // First process FIG patterns
     println!("[DEBUG] Processing text for FIG patterns:");
-    for paragraph in text.split('\n') {
+    for paragraph in &paragraphs {
         println!("[DEBUG] Processing paragraph: {}", paragraph);
         for cap in fig_pattern.captures_iter(paragraph) {
             println!("[DEBUG] Found capture: {:?}", cap.iter().map(|m| m.map(|m| m.as_str())).collect::<Vec<_>>());
@@ -476,85 +453,111 @@ pub fn process_docx(_engine: &OcrEngine, docx_path: impl AsRef<Path>, allow_2: b
         }
     }
     
-    for cap in word_pattern.captures_iter(&text) {
-        let original_word = cap.get(1).unwrap().as_str().trim();
-        let raw_number = cap.get(2).unwrap().as_str().trim();
-        
-        // Skip if the raw number doesn't contain any digits
-        if !raw_number.chars().any(|c| c.is_ascii_digit()) {
-            continue;
-        }
-        
-        // Normalize word for comparison
-        let normalized_word = normalize_text(original_word);
-        
-        // Skip unwanted patterns
-        if normalized_word.eq_ignore_ascii_case("to") ||
-           normalized_word.eq_ignore_ascii_case("than") ||
-           normalized_word.eq_ignore_ascii_case("as") ||
-           normalized_word.eq_ignore_ascii_case("the") {
-            continue;
-        }
-        
-        // Skip unwanted prefixes and FIG references
-        if normalized_word.eq_ignore_ascii_case("about") || normalized_word.eq_ignore_ascii_case("of") || 
-           normalized_word.eq_ignore_ascii_case("fig") || normalized_word.eq_ignore_ascii_case("figure") {
-            continue;
-        }
-        
-        let normalized_number = normalize_number(raw_number, allow_2, allow_3, allow_4, allow_letters, allow_hyphen);
-        if normalized_number.is_empty() {
-            continue;
-        }
-        
-        // Handle "and NUMBER" and "or NUMBER" cases
-        let is_conjunction = normalized_word.eq_ignore_ascii_case("and") || normalized_word.eq_ignore_ascii_case("or");
-        
-        // Get the display word and normalized word for this match
-        let (display_word, normalized_key_word) = if is_conjunction && !last_noun.is_empty() {
-            (last_noun.as_str(), last_noun_normalized.as_str())
-        } else {
-            (original_word, normalized_word.as_str())
-        };
+    // Figures formally declared in the figure list, and the figure each paragraph discusses
+    let figure_descriptions = figures::brief_description_of_drawings(&paragraphs);
+    let figure_contexts = figures::figure_contexts(&paragraphs, &figure_descriptions);
+
+    // Elements numbered in the claims do not support the claims themselves
+    let claims_section = claims::claims_section(&paragraphs).unwrap_or(0..0);
+    let mut described_elements = Vec::new();
+
+    // Then walk each paragraph for "element name NUMBER" phrases
+    for (paragraph_index, ((paragraph, context), origin)) in paragraphs.iter().zip(&figure_contexts).zip(&origins).enumerate() {
+        let in_claims = claims_section.contains(&paragraph_index);
+        let words: Vec<&str> = paragraph.split_whitespace().collect();
+
+        // Words already consumed as part of a numeral list
+        let mut skip_until = 0;
+
+        for (index, word) in words.iter().enumerate() {
+            // Skip if the word doesn't contain any digits
+            if index < skip_until || !word.chars().any(|c| c.is_ascii_digit()) {
+                continue;
+            }
 
-        // Create the display match and normalized key
-        let full_match = format!("{} {}", display_word, raw_number);
-        let normalized_key = format!("{} {}", normalized_key_word, normalized_number);
-        
-        // Update last noun for next iteration if not a conjunction
-        if !is_conjunction {
-            last_noun = original_word.to_string();
-            last_noun_normalized = normalized_word.clone();
-        }
-        
-        // Only add if we haven't seen this normalized match before
-        if normalized_matches.insert(normalized_key) {
-            full_matches.push(full_match);
-            numbers.insert(normalized_number);
+            let raw_number = elements::trim_numeral(word);
+            if normalize_number(raw_number, &matcher).is_empty() {
+                continue;
+            }
+
+            // Get the display name and normalized name for this match
+            let name_words = elements::element_name_before(&words, index);
+            if name_words.is_empty() {
+                continue;
+            }
+            let display_name = name_words.join(" ");
+            let normalized_name = elements::normalize_element_name(&display_name);
+
+            // Expand lists and ranges like "110a, 110b, and 110c" or "120-124"
+            let (items, last_index) = elements::numeral_list(&words, index);
+            skip_until = last_index + 1;
+            let mut listed = Vec::new();
+            for (start, end) in &items {
+                let expanded = elements::expand_item(start, end.as_deref());
+                let plain_range = expanded.len() > 1 && expanded.iter().all(|n| n.chars().all(|c| c.is_ascii_digit()));
+                for (position, numeral) in expanded.iter().enumerate() {
+                    let normalized_number = normalize_number(numeral, &matcher);
+                    if !normalized_number.is_empty() {
+                        let interior = plain_range && position > 0 && position + 1 < expanded.len();
+                        listed.push((numeral.clone(), normalized_number, interior));
+                    }
+                }
+            }
+
+            // Remember where expanded numerals came from
+            let expanded_from = if listed.len() > 1 || items.iter().any(|(_, end)| end.is_some()) {
+                let source = words[index..=last_index].join(" ");
+                Some(source.trim_end_matches(|c: char| ",;:.".contains(c)).to_string())
+            } else {
+                None
+            };
+
+            for (display_number, normalized_number, range_interior) in listed {
+                // Create the display match and normalized key
+                let full_match = format!("{} {}", display_name, display_number);
+                let normalized_key = format!("{} {}", normalized_name, normalized_number);
+
+                // Record the element again for each new figure context it is discussed in
+                let context_key = format!("{} {:?}", normalized_key, context);
+                if seen_in_context.insert(context_key) {
+                    for numeral in normalized_number.split_whitespace() {
+                        let element = SpecElement {
+                            name: normalized_name.clone(),
+                            numeral: numeral.to_string(),
+                            text: full_match.clone(),
+                            expanded_from: expanded_from.clone(),
+                            range_interior,
+                            figures: context.clone(),
+                            origin: *origin,
+                        };
+                        if !in_claims {
+                            described_elements.push(element.clone());
+                        }
+                        spec_elements.push(element);
+                    }
+                }
+
+                // Only add if we haven't seen this normalized match before
+                if normalized_matches.insert(normalized_key) {
+                    full_matches.push(full_match);
+                    numbers.insert(normalized_number);
+                }
+            }
         }
     }
 
-    // Sort the full matches alphabetically by the word before the number
+    // Sort the full matches alphabetically by the element name, then by number
     full_matches.sort_by(|a, b| {
-        // Extract the word part (everything before the number)
-        let a_word = a.split_whitespace().next().unwrap_or("").to_lowercase();
-        let b_word = b.split_whitespace().next().unwrap_or("").to_lowercase();
+        // Split off the number (the last word) from the name before it
+        let (a_name, a_num) = a.rsplit_once(' ').unwrap_or(("", a));
+        let (b_name, b_num) = b.rsplit_once(' ').unwrap_or(("", b));
 
-        // First compare words
-        match a_word.cmp(&b_word) {
+        // First compare names
+        match a_name.to_lowercase().cmp(&b_name.to_lowercase()) {
             std::cmp::Ordering::Equal => {
-                // If words are the same, compare numbers
-                let a_num = word_pattern.captures(a)
-                    .and_then(|cap| cap.get(1))
-                    .map(|m| m.as_str())
-                    .unwrap_or("");
-                let b_num = word_pattern.captures(b)
-                    .and_then(|cap| cap.get(1))
-                    .map(|m| m.as_str())
-                    .unwrap_or("");
-
-                let a_val = a_num.chars().take_while(|c| c.is_digit(10)).collect::<String>();
-                let b_val = b_num.chars().take_while(|c| c.is_digit(10)).collect::<String>();
+                // If names are the same, compare numbers
+                let a_val = a_num.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
+                let b_val = b_num.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
 
                 match (a_val.parse::<i32>(), b_val.parse::<i32>()) {
                     (Ok(a_val), Ok(b_val)) => a_val.cmp(&b_val),
@@ -571,8 +574,8 @@ pub fn process_docx(_engine: &OcrEngine, docx_path: impl AsRef<Path>, allow_2: b
     // Convert numbers set to sorted vector
     let mut numbers_vec: Vec<String> = numbers.into_iter().collect();
     numbers_vec.sort_by(|a, b| {
-        let a_val = a.chars().take_while(|c| c.is_digit(10)).collect::<String>();
-        let b_val = b.chars().take_while(|c| c.is_digit(10)).collect::<String>();
+        let a_val = a.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
+        let b_val = b.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
 
         match (a_val.parse::<i32>(), b_val.parse::<i32>()) {
             (Ok(a_val), Ok(b_val)) => a_val.cmp(&b_val),
@@ -586,36 +589,174 @@ pub fn process_docx(_engine: &OcrEngine, docx_path: impl AsRef<Path>, allow_2: b
     println!("[DEBUG] DOCX final full_matches after sort: {:?}", full_matches);
     println!("[DEBUG] DOCX final numbers after sort: {:?}", numbers_vec);
 
+    // Figures the detailed description mentions
+    let detailed_description_figures = figures::detailed_description_figures(&paragraphs, &figure_descriptions);
+
+    // Split the claims and look up each claimed element in the description
+    let claims = claims::parse_claims(&paragraphs, &described_elements);
+    let unsupported_claim_terms = claims::claim_term_references(&claims, |term| term.numerals.is_empty());
+    let antecedent_issues = claims::antecedent_issues(&claims);
+    let (paragraphs, origins) = if checked == revisions { (paragraphs, origins) } else { read(revisions)? };
+
     Ok(DocxResult {
         full_matches,
         numbers: numbers_vec,
-        paragraphs
+        paragraphs,
+        origins,
+        elements: spec_elements,
+        figure_descriptions,
+        detailed_description_figures,
+        claims,
+        unsupported_claim_terms,
+        antecedent_issues,
     })
 }
 
-pub fn process_pdf(engine: &OcrEngine, pdf_path: impl AsRef<Path>) -> Result<Vec<(RgbImage, Vec<OcrResult>)>> {
+/// OCR a prepared sheet, straightening it first if it is skewed.
+/// The boxes are reported in the coordinates of the sheet as given.
+fn ocr_sheet(engine: &OcrEngine, img: RgbImage, grammar: &LabelGrammar, options: &RenderOptions) -> Result<Vec<OcrResult>> {
+    let (straightened, deskew) = if options.deskew {
+        preprocess::deskew(&img)
+    } else {
+        (img, None)
+    };
+
+    let mut ocr_results = process_page(engine, straightened, grammar, options)?;
+    if let Some(deskew) = deskew {
+        for result in &mut ocr_results {
+            result.bbox = deskew.bbox_to_original(result.bbox);
+            result.quad = deskew.quad_to_original(result.quad);
+        }
+    }
+    Ok(ocr_results)
+}
+
+/// OCR the selected sheets of TIFF (single or multi-page), PNG or JPEG drawing files.
+/// Sheets are numbered from 1 across all files in order, and page selection applies to that numbering.
+pub fn process_images(engine: &OcrEngine, paths: &[impl AsRef<Path>], grammar: &LabelGrammar, options: &RenderOptions) -> Result<Vec<(usize, RgbImage, Vec<OcrResult>)>> {
+    options.validate()?;
+
+    // Index every sheet by its file and its page within the file
+    let mut sheets = Vec::new();
+    for path in paths {
+        let count = sheets::sheet_count(path)
+            .context(format!("Failed to read {}", path.as_ref().display()))?;
+        sheets.extend((0..count).map(|index| (path.as_ref(), index)));
+    }
+    let selected = options.selected_pages(sheets.len())?;
+    println!("[DEBUG] OCR of {} of {} drawing sheets", selected.len(), sheets.len());
+
+    let mut results = Vec::new();
+    for sheet_index in selected {
+        let (path, index) = sheets[sheet_index];
+        let gray = sheets::load_sheet(path, index)
+            .context(format!("Failed to load sheet {} of {}", index + 1, path.display()))?;
+        let img = sheets::prepare_sheet(gray, options);
+
+        let ocr_results = ocr_sheet(engine, img.clone(), grammar, options)
+            .context(format!("Failed to process sheet {}", sheet_index + 1))?;
+        results.push((sheet_index + 1, img, ocr_results));
+    }
+
+    Ok(results)
+}
+
+/// OCR the selected pages of a PDF. Each entry holds the 1-based page number,
+/// the rendered image and the OCR results for that page.
+pub fn process_pdf(engine: &OcrEngine, pdf_path: impl AsRef<Path>, grammar: &LabelGrammar, options: &RenderOptions) -> Result<Vec<(usize, RgbImage, Vec<OcrResult>)>> {
+    options.validate()?;
+
     // Open PDF document
     let doc = Document::open(pdf_path.as_ref().to_str().unwrap())
         .context("Failed to open PDF file")?;
 
-    // Get number of pages
+    // Get number of pages and pick the ones to OCR
     let page_count = doc.page_count()
         .context("Failed to get page count")?;
+    let selected = options.selected_pages(page_count as usize)?;
+    println!("[DEBUG] OCR of {} of {} pages at {} DPI", selected.len(), page_count, options.dpi);
 
     let mut results = Vec::new();
 
     // Process each page
-    for page_num in 0..page_count {
-        // Convert PDF page to image (300 DPI for optimal quality)
-        let img = pdf_page_to_image(&doc, page_num, 300.0)
+    for page_index in selected {
+        let page_num = page_index as i32;
+
+        // Text objects in vector drawings are exact, so OCR only reads what the text layer lacks
+        let text_layer_results = if options.text_layer {
+            text_layer::page_words(&doc, page_num)
+                .context(format!("Failed to read the text layer of page {}", page_num + 1))?
+        } else {
+            Vec::new()
+        };
+
+        // Convert PDF page to image
+        let img = pdf_page_to_image(&doc, page_num, options)
             .context(format!("Failed to convert page {} to image", page_num + 1))?;
+        let mut ocr_image = img.clone();
+        text_layer::mask_words(&mut ocr_image, &text_layer_results);
 
         // Process the page and extract text with bounding boxes
-        let ocr_results = process_page(engine, img.clone())
+        let mut ocr_results = ocr_sheet(engine, ocr_image, grammar, options)
             .context(format!("Failed to process page {}", page_num + 1))?;
 
-        results.push((img, ocr_results));
+        // OCR lines are numbered after those of the text layer
+        let line_offset = text_layer_results.iter().map(|result| result.line + 1).max().unwrap_or(0);
+        for result in &mut ocr_results {
+            result.line += line_offset;
+        }
+        let mut page_results = text_layer_results;
+        page_results.extend(ocr_results);
+
+        results.push((page_index + 1, img, page_results));
     }
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_spec(name: &str, text: &str) -> DocxResult {
+        let path = std::env::temp_dir().join(format!("spec_{}_{}.txt", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        let result = process_spec(&SpecSource::Text(path.clone()), &LabelGrammar::default(), RevisionView::Final).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn only_range_interiors_are_marked() {
+        let result = text_spec("ranges", "The walls 120-124 and legs 130, 132 and 134 meet.");
+        let interior: Vec<(&str, bool)> = result.elements.iter()
+            .map(|element| (element.numeral.as_str(), element.range_interior))
+            .collect();
+        assert_eq!(interior, vec![
+            ("120", false), ("121", true), ("122", true), ("123", true), ("124", false),
+            ("130", false), ("132", false), ("134", false),
+        ]);
+    }
+
+    #[test]
+    fn markup_is_shown_but_numerals_come_from_the_final_text() {
+        use docx_rs::{Delete, Docx, Insert, Paragraph, Run};
+
+        let path = std::env::temp_dir().join(format!("spec_{}_markup.docx", std::process::id()));
+        Docx::new()
+            .add_paragraph(Paragraph::new()
+                .add_run(Run::new().add_text("The arm 10"))
+                .add_delete(Delete::new().add_run(Run::new().add_delete_text("6")))
+                .add_insert(Insert::new(Run::new().add_text("8")))
+                .add_run(Run::new().add_text(" pivots on the pin"))
+                .add_delete(Delete::new().add_run(Run::new().add_delete_text(" 112")))
+                .add_run(Run::new().add_text(".")))
+            .build()
+            .pack(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        let result = process_spec(&SpecSource::Docx(path.clone()), &LabelGrammar::default(), RevisionView::Markup).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(result.paragraphs, vec!["The arm 10 [- 6 -] [+ 8 +] pivots on the pin [- 112 -] ."]);
+        assert_eq!(result.full_matches, vec!["arm 108"]);
+    }
+}
@@ -1,25 +1,47 @@
 use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use ocrs::{OcrEngine, OcrEngineParams};
 
 struct Args {
-    pdf_path: String,
+    drawing_path: String,
+    spec_path: Option<String>,
+    revisions: ocr_app::RevisionView,
+    profile_path: Option<String>,
+    render: ocr_app::RenderOptions,
 }
 
 fn parse_args() -> Result<Args, lexopt::Error> {
     use lexopt::prelude::*;
 
     let mut values = VecDeque::new();
+    let mut spec_path = None;
+    let mut revisions = ocr_app::RevisionView::default();
+    let mut profile_path = None;
+    let mut render = ocr_app::RenderOptions::default();
     let mut parser = lexopt::Parser::from_env();
 
     while let Some(arg) = parser.next()? {
         match arg {
             Value(val) => values.push_back(val.string()?),
+            Long("spec" | "docx") => spec_path = Some(parser.value()?.string()?),
+            Long("revisions") => revisions = parser.value()?.parse()?,
+            Long("profile") => profile_path = Some(parser.value()?.string()?),
+            Long("binarization") => render.binarization = parser.value()?.parse()?,
+            Long("dpi") => render.dpi = parser.value()?.parse()?,
+            Long("pages") => {
+                render.pages = ocr_app::render::parse_page_ranges(&parser.value()?.string()?)
+                    .map_err(|e| lexopt::Error::Custom(e.into()))?;
+            }
+            Long("max-pixels") => render.max_pixels = Some(parser.value()?.parse()?),
+            Long("no-deskew") => render.deskew = false,
+            Long("tile-size") => render.tile_size = Some(parser.value()?.parse()?),
+            Long("tile-overlap") => render.tile_overlap = parser.value()?.parse()?,
+            Long("no-text-layer") => render.text_layer = false,
             Long("help") => {
                 println!(
-                    "Usage: {bin_name} <pdf_file>",
+                    "Usage: {bin_name} [--spec <docx|odt|rtf|txt|md|pdf file>] [--revisions <final|original|markup>] [--profile <label_profile.toml>] [--binarization <otsu|sauvola|none>] [--dpi <dpi>] [--pages <1-3,7>] [--max-pixels <n>] [--no-deskew] [--tile-size <px>] [--tile-overlap <px>] [--no-text-layer] <pdf_or_image_file>",
                     bin_name = parser.bin_name().unwrap_or("ocr_app")
                 );
                 std::process::exit(0);
@@ -28,9 +50,9 @@ fn parse_args() -> Result<Args, lexopt::Error> {
         }
     }
 
-    let pdf_path = values.pop_front().ok_or("missing PDF file path")?;
+    let drawing_path = values.pop_front().ok_or("missing PDF or drawing image path")?;
 
-    Ok(Args { pdf_path })
+    Ok(Args { drawing_path, spec_path, revisions, profile_path, render })
 }
 
 /// Given a file path relative to the crate root, return the absolute path.
@@ -61,13 +83,25 @@ fn main() -> Result<()> {
         ..Default::default()
     }).map_err(|e| anyhow::anyhow!("Failed to initialize OCR engine: {}", e))?;
 
-    // Process PDF and get text and images from all pages
-    let results = ocr_app::process_pdf(&engine, &args.pdf_path)
-        .context("Failed to process PDF")?;
+    // Load the label grammar, falling back to the default one
+    let grammar = match &args.profile_path {
+        Some(path) => ocr_app::LabelGrammar::from_toml_file(path)?,
+        None => ocr_app::LabelGrammar::default(),
+    };
+
+    // Process the PDF, or drawing sheets given as TIFF, PNG or JPEG, and get text and images from all pages
+    let is_pdf = Path::new(&args.drawing_path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    let results = if is_pdf {
+        ocr_app::process_pdf(&engine, &args.drawing_path, &grammar, &args.render)
+            .context("Failed to process PDF")?
+    } else {
+        ocr_app::process_images(&engine, &[&args.drawing_path], &grammar, &args.render)
+            .context("Failed to process drawing sheets")?
+    };
 
     // Print extracted text and save images
-    for (i, (image, ocr_results)) in results.iter().enumerate() {
-        println!("Text from page {}:", i + 1);
+    for (page, image, ocr_results) in &results {
+        println!("Text from page {}:", page);
         for result in ocr_results {
             println!("{}", result.text);
         }
@@ -109,8 +143,17 @@ fn main() -> Result<()> {
         }
 
         // Save the image
-        output_image.save(format!("output_page_{}.png", i + 1))
-            .context(format!("Failed to save output image for page {}", i + 1))?;
+        output_image.save(format!("output_page_{}.png", page))
+            .context(format!("Failed to save output image for page {}", page))?;
+    }
+
+    // Cross-check against the specification when one was given
+    if let Some(spec_path) = &args.spec_path {
+        let source = ocr_app::SpecSource::detect(spec_path)?;
+        let docx_results = ocr_app::process_spec(&source, &grammar, args.revisions)
+            .context(format!("Failed to process {} specification", source.format_name()))?;
+        let report = ocr_app::crosscheck::cross_check(&results, &docx_results, &grammar)?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
     }
 
     Ok(())
@@ -0,0 +1,295 @@
+use anyhow::Result;
+use image::{imageops, RgbImage};
+use ocrs::{ImageSource, OcrEngine, TextLine};
+use regex::Regex;
+use rten_imageproc::{find_contours, min_area_rect, simplify_polygon, Point, RetrievalMode, RotatedRect};
+use rten_tensor::prelude::*;
+use rten_tensor::NdTensor;
+
+use crate::{rotated, LabelGrammar, FIG_PATTERN};
+
+/// Clockwise rotations tried for each sheet, in degrees
+pub const ROTATIONS: [u32; 4] = [0, 90, 180, 270];
+
+/// Text probability above which a pixel belongs to a word, as in ocrs' word detection
+const TEXT_THRESHOLD: f32 = 0.2;
+
+/// Distance word boxes are grown by around their text pixels, as in ocrs' word detection
+const WORD_EXPAND_DIST: f32 = 3.0;
+
+/// Smallest word box area kept, in pixels, as in ocrs' word detection
+const MIN_WORD_AREA: f32 = 100.0;
+
+/// A sheet recognized in the orientation that read best
+pub struct RecognizedPage {
+    pub rotation: u32,                        // Clockwise rotation applied before OCR, in degrees
+    pub image: RgbImage,                      // The rotated image the lines were found in
+    pub line_rects: Vec<Vec<RotatedRect>>,    // Word boxes of each line, in rotated coordinates
+    pub line_texts: Vec<Option<TextLine>>,    // Recognized text of each line
+    pub rotated_words: Vec<RotatedRect>,      // Tilted or vertical words, left out of the lines
+    pub text_pixels: NdTensor<f32, 2>,        // Probability that each pixel of the rotated image is text
+}
+
+/// Rotate an image clockwise by a multiple of 90 degrees
+pub fn rotate(img: &RgbImage, rotation: u32) -> RgbImage {
+    match rotation {
+        90 => imageops::rotate90(img),
+        180 => imageops::rotate180(img),
+        270 => imageops::rotate270(img),
+        _ => img.clone(),
+    }
+}
+
+/// Map a normalized [x1, y1, x2, y2] box from the rotated image back onto the original page
+pub fn unrotate_bbox(bbox: [f32; 4], rotation: u32) -> [f32; 4] {
+    let [x1, y1, x2, y2] = bbox;
+    match rotation {
+        // A point (x, y) on the original lands on (1 - y, x) after turning clockwise
+        90 => [y1, 1.0 - x2, y2, 1.0 - x1],
+        180 => [1.0 - x2, 1.0 - y2, 1.0 - x1, 1.0 - y1],
+        270 => [1.0 - y2, x1, 1.0 - y1, x2],
+        _ => bbox,
+    }
+}
+
+/// Map a normalized point from the rotated image back onto the original page
+pub fn unrotate_point([x, y]: [f32; 2], rotation: u32) -> [f32; 2] {
+    match rotation {
+        90 => [y, 1.0 - x],
+        180 => [1.0 - x, 1.0 - y],
+        270 => [1.0 - y, x],
+        _ => [x, y],
+    }
+}
+
+/// Rotate a sheet, then detect words, group them into lines and recognize each line
+pub fn recognize(engine: &OcrEngine, img: &RgbImage, rotation: u32) -> Result<RecognizedPage> {
+    let img = rotate(img, rotation);
+
+    // Convert image to OCR input format
+    let img_source = ImageSource::from_bytes(img.as_raw(), img.dimensions())
+        .map_err(|e| anyhow::anyhow!("Failed to create image source: {}", e))?;
+    let ocr_input = engine.prepare_input(img_source)
+        .map_err(|e| anyhow::anyhow!("Failed to prepare OCR input: {}", e))?;
+
+    // Detect words and group into lines, keeping the text probabilities to score each word by
+    let text_pixels = engine.detect_text_pixels(&ocr_input)
+        .map_err(|e| anyhow::anyhow!("Failed to detect words: {}", e))?;
+    let word_rects = word_rects(&text_pixels);
+    let lines = engine.find_text_lines(&ocr_input, &word_rects);
+
+    // Tilted and vertical words are recognized one by one later, so keep them out of the lines
+    let mut line_rects = Vec::new();
+    let mut rotated_words = Vec::new();
+    for line in lines {
+        let (rotated, level): (Vec<RotatedRect>, Vec<RotatedRect>) = line.into_iter().partition(rotated::is_rotated);
+        rotated_words.extend(rotated);
+        if !level.is_empty() {
+            line_rects.push(level);
+        }
+    }
+
+    // Recognize text in each line
+    let line_texts = engine.recognize_text(&ocr_input, &line_rects)
+        .map_err(|e| anyhow::anyhow!("Failed to recognize text: {}", e))?;
+
+    Ok(RecognizedPage { rotation, image: img, line_rects, line_texts, rotated_words, text_pixels })
+}
+
+/// Boxes around the connected areas of text pixels, the way `OcrEngine::detect_words` finds
+/// them, so the probability map it works from can be kept without running detection twice
+fn word_rects(text_pixels: &NdTensor<f32, 2>) -> Vec<RotatedRect> {
+    let mask = text_pixels.map(|probability| *probability > TEXT_THRESHOLD);
+    find_contours(mask.view(), RetrievalMode::External)
+        .iter()
+        .filter_map(|polygon| {
+            let points: Vec<_> = polygon.iter().map(|point| point.to_f32()).collect();
+            min_area_rect(&simplify_polygon(&points, 2.0)).map(|mut rect| {
+                rect.resize(rect.width() + 2.0 * WORD_EXPAND_DIST, rect.height() + 2.0 * WORD_EXPAND_DIST);
+                rect
+            })
+        })
+        .filter(|rect| rect.area() >= MIN_WORD_AREA)
+        .collect()
+}
+
+/// How sure the detection model is that a word box holds text: the mean text probability of
+/// the pixels inside it, from 0 to 1. Faint, broken or smudged characters score low, which
+/// is where misreads come from; ocrs reports no per-character probabilities to use instead.
+pub fn word_confidence(text_pixels: &NdTensor<f32, 2>, rect: &RotatedRect) -> f32 {
+    let [height, width] = text_pixels.shape();
+    let corners = rect.corners();
+    let min = |f: fn(&Point<f32>) -> f32| corners.iter().map(f).fold(f32::MAX, f32::min).max(0.0) as usize;
+    let max = |f: fn(&Point<f32>) -> f32, limit: usize| (corners.iter().map(f).fold(f32::MIN, f32::max).ceil().max(0.0) as usize).min(limit);
+
+    let (mut sum, mut count) = (0.0, 0);
+    for y in min(|p| p.y)..max(|p| p.y, height) {
+        for x in min(|p| p.x)..max(|p| p.x, width) {
+            if rect.contains(Point::from_yx(y as f32 + 0.5, x as f32 + 0.5)) {
+                sum += text_pixels[[y, x]];
+                count += 1;
+            }
+        }
+    }
+    if count == 0 { 0.0 } else { sum / count as f32 }
+}
+
+/// The FIG. and label patterns words are scored against, compiled once per page
+pub struct LabelMatcher<'a> {
+    pub grammar: &'a LabelGrammar,
+    pub fig_regex: Regex,
+    pub label_regex: Regex,
+}
+
+impl<'a> LabelMatcher<'a> {
+    pub fn new(grammar: &'a LabelGrammar) -> Result<Self> {
+        Ok(Self { grammar, fig_regex: Regex::new(FIG_PATTERN).unwrap(), label_regex: grammar.regex()? })
+    }
+}
+
+/// How readable a set of lines is. ocrs does not report character probabilities,
+/// so a recognition counts as readable when it is a valid label or FIG. reference,
+/// and somewhat less so when it reads as a plausible word. Sideways or upside-down
+/// text comes back as fragments that score neither.
+pub fn readability(line_texts: &[Option<TextLine>], matcher: &LabelMatcher) -> f32 {
+    let mut score = 0.0;
+    for line in line_texts.iter().flatten() {
+        let text = line.to_string();
+        score += 3.0 * matcher.fig_regex.find_iter(&text).count() as f32;
+
+        for word in text.split_whitespace() {
+            let cleaned = matcher.grammar.clean_token(word);
+            if matcher.label_regex.is_match(&cleaned) {
+                score += 2.0;
+            } else if is_plausible_word(&cleaned) {
+                score += 1.0;
+            }
+        }
+    }
+    score
+}
+
+/// Whether a token reads like a word: three or more letters, a vowel, and sensible capitalization
+fn is_plausible_word(token: &str) -> bool {
+    let letters: Vec<char> = token.chars().filter(|c| c.is_alphabetic()).collect();
+    letters.len() >= 3
+        && letters.len() == token.chars().count()
+        && letters.iter().any(|c| "aeiouyAEIOUY".contains(*c))
+        && (letters[1..].iter().all(|c| c.is_lowercase()) || letters.iter().all(|c| c.is_uppercase()))
+}
+
+/// How well a recognized word fits what drawings contain, from 0 to 1. This is not a
+/// recognition confidence: ocrs reports no character probabilities, so a misread that
+/// happens to form a valid label, such as "106" for "108", still scores high. Labels and
+/// FIG. references read exactly score highest, readings that only fit once stray
+/// punctuation is dropped or that look like run-together labels score lower, and digit
+/// fragments that fit nothing score lowest.
+pub fn label_fit(word: &str, matcher: &LabelMatcher) -> f32 {
+    if matcher.fig_regex.is_match(word) {
+        return 1.0;
+    }
+
+    let label_regex = &matcher.label_regex;
+    let cleaned = matcher.grammar.clean_token(word);
+    if label_regex.is_match(word) {
+        0.95
+    } else if label_regex.is_match(&cleaned) || is_plausible_word(&cleaned) {
+        0.8
+    } else if !crate::split_merged_label(&cleaned, label_regex).is_empty() {
+        0.5
+    } else if cleaned.chars().any(|c| c.is_ascii_digit()) {
+        0.3
+    } else {
+        0.2
+    }
+}
+
+/// Recognize a sheet in each of the four orientations and keep the one that reads best.
+/// The upright orientation wins ties, so portrait sheets are never turned needlessly.
+pub fn recognize_upright(engine: &OcrEngine, img: RgbImage, grammar: &LabelGrammar) -> Result<RecognizedPage> {
+    let matcher = LabelMatcher::new(grammar)?;
+    let mut best: Option<(f32, RecognizedPage)> = None;
+    for rotation in ROTATIONS {
+        let page = recognize(engine, &img, rotation)?;
+        let score = readability(&page.line_texts, &matcher);
+        println!("[DEBUG] Orientation {}: score {:.1}", rotation, score);
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, page));
+        }
+    }
+
+    let (_, best) = best.unwrap();
+    if best.rotation != 0 {
+        println!("[DEBUG] Rotating sheet {} degrees clockwise for OCR", best.rotation);
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rten_imageproc::Rect;
+
+    #[test]
+    fn labels_and_figure_references_fit_best() {
+        let grammar = LabelGrammar::default();
+        let matcher = LabelMatcher::new(&grammar).unwrap();
+        assert_eq!(label_fit("FIG. 3", &matcher), 1.0);
+        assert_eq!(label_fit("102", &matcher), 0.95);
+        assert_eq!(label_fit("102,", &matcher), 0.8);
+        assert_eq!(label_fit("housing", &matcher), 0.8);
+    }
+
+    #[test]
+    fn fragments_fit_poorly() {
+        let grammar = LabelGrammar::default();
+        let matcher = LabelMatcher::new(&grammar).unwrap();
+        assert_eq!(label_fit("1O2", &matcher), 0.3);
+        assert_eq!(label_fit("~", &matcher), 0.2);
+    }
+
+    fn text_block(top: usize, left: usize, height: usize, width: usize, probability: f32) -> NdTensor<f32, 2> {
+        let mut text_pixels = NdTensor::zeros([60, 100]);
+        for y in top..top + height {
+            for x in left..left + width {
+                text_pixels[[y, x]] = probability;
+            }
+        }
+        text_pixels
+    }
+
+    #[test]
+    fn word_confidence_is_the_mean_probability_in_the_box() {
+        let text_pixels = text_block(20, 20, 20, 40, 0.9);
+        let inside = RotatedRect::from_rect(Rect::from_tlhw(20.0, 20.0, 20.0, 40.0));
+        assert!((word_confidence(&text_pixels, &inside) - 0.9).abs() < 1e-5);
+
+        // Half the box is background
+        let half = RotatedRect::from_rect(Rect::from_tlhw(20.0, 40.0, 20.0, 40.0));
+        assert!((word_confidence(&text_pixels, &half) - 0.45).abs() < 1e-5);
+
+        let outside = RotatedRect::from_rect(Rect::from_tlhw(-30.0, -30.0, 10.0, 10.0));
+        assert_eq!(word_confidence(&text_pixels, &outside), 0.0);
+    }
+
+    #[test]
+    fn word_rects_surround_text_pixels() {
+        let rects = word_rects(&text_block(20, 20, 10, 30, 0.8));
+        assert_eq!(rects.len(), 1);
+        let (min_x, max_x) = rects[0].corners().iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.x), hi.max(p.x)));
+        assert!(min_x < 20.0 && max_x > 49.0, "{} {}", min_x, max_x);
+
+        // Faint pixels and specks are not words
+        assert!(word_rects(&text_block(20, 20, 10, 30, 0.1)).is_empty());
+        assert!(word_rects(&text_block(20, 20, 3, 3, 0.8)).is_empty());
+    }
+
+    #[test]
+    fn plausible_words() {
+        assert!(is_plausible_word("Housing"));
+        assert!(is_plausible_word("SHAFT"));
+        assert!(!is_plausible_word("hOUSING"));
+        assert!(!is_plausible_word("xqz"));
+        assert!(!is_plausible_word("ab"));
+    }
+}
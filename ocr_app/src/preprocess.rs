@@ -0,0 +1,325 @@
+use image::{GrayImage, Luma, Rgb, RgbImage};
+
+/// Half-width of the Sauvola window in pixels, about 1.3 mm at 300 DPI
+const SAUVOLA_RADIUS: u32 = 15;
+
+/// Sauvola sensitivity; lower keeps more of light-gray strokes
+const SAUVOLA_K: f64 = 0.2;
+
+/// Dynamic range of the standard deviation for 8-bit images
+const SAUVOLA_R: f64 = 128.0;
+
+/// How a rendered page is turned into black and white before OCR
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Binarization {
+    /// One global threshold picked from the page histogram; suits clean vector PDFs
+    #[default]
+    Otsu,
+    /// A threshold per pixel from its neighbourhood; suits scans and shaded drawings
+    Sauvola,
+    /// Keep the grayscale rendering as is, e.g. for color-rendered CAD exports
+    None,
+}
+
+impl std::str::FromStr for Binarization {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "otsu" => Ok(Self::Otsu),
+            "sauvola" | "adaptive" => Ok(Self::Sauvola),
+            "none" => Ok(Self::None),
+            _ => Err(anyhow::anyhow!("Unknown binarization mode: {} (expected otsu, sauvola or none)", s)),
+        }
+    }
+}
+
+/// Binarize a grayscale page with the chosen mode
+pub fn binarize(gray: &GrayImage, mode: Binarization) -> GrayImage {
+    match mode {
+        Binarization::Otsu => {
+            let threshold = otsu_threshold(gray);
+            println!("[DEBUG] Otsu threshold: {}", threshold);
+            threshold_image(gray, |_, _| threshold as f64)
+        }
+        Binarization::Sauvola => sauvola(gray),
+        Binarization::None => gray.clone(),
+    }
+}
+
+/// Map pixels at or below the threshold to black and the rest to white
+fn threshold_image(gray: &GrayImage, threshold: impl Fn(u32, u32) -> f64) -> GrayImage {
+    GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+        let value = gray.get_pixel(x, y)[0] as f64;
+        Luma([if value <= threshold(x, y) { 0 } else { 255 }])
+    })
+}
+
+/// Threshold that maximizes the between-class variance of the histogram
+pub fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+
+    let total = gray.pixels().len() as f64;
+    let sum_all: f64 = histogram.iter().enumerate().map(|(value, &count)| value as f64 * count as f64).sum();
+
+    let mut best = (0u8, 0.0f64);
+    let mut background_weight = 0.0;
+    let mut background_sum = 0.0;
+    for (value, &count) in histogram.iter().enumerate() {
+        background_weight += count as f64;
+        background_sum += value as f64 * count as f64;
+        let foreground_weight = total - background_weight;
+        if background_weight == 0.0 || foreground_weight == 0.0 {
+            continue;
+        }
+
+        let background_mean = background_sum / background_weight;
+        let foreground_mean = (sum_all - background_sum) / foreground_weight;
+        let variance = background_weight * foreground_weight * (background_mean - foreground_mean).powi(2);
+        if variance > best.1 {
+            best = (value as u8, variance);
+        }
+    }
+    best.0
+}
+
+/// Sauvola local thresholding using integral images of the values and their squares
+fn sauvola(gray: &GrayImage) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let stride = width as usize + 1;
+
+    // Integral images with a zero row and column in front
+    let mut sums = vec![0f64; stride * (height as usize + 1)];
+    let mut squares = vec![0f64; stride * (height as usize + 1)];
+    for y in 0..height as usize {
+        let mut row_sum = 0.0;
+        let mut row_squares = 0.0;
+        for x in 0..width as usize {
+            let value = gray.get_pixel(x as u32, y as u32)[0] as f64;
+            row_sum += value;
+            row_squares += value * value;
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+            squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_squares;
+        }
+    }
+
+    let window = |table: &[f64], x0: usize, y0: usize, x1: usize, y1: usize| {
+        table[y1 * stride + x1] - table[y0 * stride + x1] - table[y1 * stride + x0] + table[y0 * stride + x0]
+    };
+
+    threshold_image(gray, |x, y| {
+        let x0 = x.saturating_sub(SAUVOLA_RADIUS) as usize;
+        let y0 = y.saturating_sub(SAUVOLA_RADIUS) as usize;
+        let x1 = (x + SAUVOLA_RADIUS + 1).min(width) as usize;
+        let y1 = (y + SAUVOLA_RADIUS + 1).min(height) as usize;
+        let count = ((x1 - x0) * (y1 - y0)) as f64;
+
+        let mean = window(&sums, x0, y0, x1, y1) / count;
+        let variance = (window(&squares, x0, y0, x1, y1) / count - mean * mean).max(0.0);
+        mean * (1.0 + SAUVOLA_K * (variance.sqrt() / SAUVOLA_R - 1.0))
+    })
+}
+
+/// Largest skew corrected, in degrees; anything beyond is a deliberate rotation
+const MAX_SKEW_DEGREES: f32 = 5.0;
+
+/// Skew below this is left alone, in degrees
+const MIN_SKEW_DEGREES: f32 = 0.05;
+
+/// Only every n-th pixel in each direction is sampled when estimating skew
+const SKEW_SAMPLE_STRIDE: u32 = 2;
+
+/// A deskew applied to a page, kept so boxes found on the straightened image can be
+/// reported in the coordinates of the page as rendered
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Deskew {
+    pub angle: f32,   // Skew of the page content in degrees, clockwise positive
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Deskew {
+    /// Map a point on the straightened image to the page as rendered, both in pixels
+    fn point_to_original(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let (cx, cy) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+        let (dx, dy) = (x - cx, y - cy);
+        (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+    }
+
+    /// Map a normalized [x1, y1, x2, y2] box from the straightened image back to the page
+    pub fn bbox_to_original(&self, bbox: [f32; 4]) -> [f32; 4] {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let corners = [(bbox[0], bbox[1]), (bbox[2], bbox[1]), (bbox[0], bbox[3]), (bbox[2], bbox[3])]
+            .map(|(x, y)| self.point_to_original(x * width, y * height));
+
+        let mut mapped = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+        for (x, y) in corners {
+            mapped[0] = mapped[0].min(x / width);
+            mapped[1] = mapped[1].min(y / height);
+            mapped[2] = mapped[2].max(x / width);
+            mapped[3] = mapped[3].max(y / height);
+        }
+        mapped.map(|v| v.clamp(0.0, 1.0))
+    }
+
+    /// Map normalized quad corners from the straightened image back to the page
+    pub fn quad_to_original(&self, quad: [[f32; 2]; 4]) -> [[f32; 2]; 4] {
+        let (width, height) = (self.width as f32, self.height as f32);
+        quad.map(|[x, y]| {
+            let (x, y) = self.point_to_original(x * width, y * height);
+            [x / width, y / height]
+        })
+    }
+}
+
+/// How sharply the dark pixels line up into rows when the page is straightened by `angle`.
+/// Text lines and leader lines give tall, narrow peaks in the row profile when level.
+fn projection_sharpness(dark: &[(f32, f32)], angle: f32, height: u32) -> f64 {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let mut rows = vec![0u32; height as usize * 2];
+    for &(dx, dy) in dark {
+        let row = (-dx * sin + dy * cos + height as f32) as usize;
+        if let Some(count) = rows.get_mut(row) {
+            *count += 1;
+        }
+    }
+    rows.iter().map(|&count| (count as f64).powi(2)).sum()
+}
+
+/// Estimate the skew of a page in degrees by searching for the angle whose row profile is sharpest
+pub fn estimate_skew(img: &RgbImage) -> f32 {
+    let (width, height) = img.dimensions();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+
+    // Dark pixels relative to the page center
+    let mut dark = Vec::new();
+    for y in (0..height).step_by(SKEW_SAMPLE_STRIDE as usize) {
+        for x in (0..width).step_by(SKEW_SAMPLE_STRIDE as usize) {
+            if img.get_pixel(x, y)[0] < 128 {
+                dark.push((x as f32 - cx, y as f32 - cy));
+            }
+        }
+    }
+    if dark.is_empty() {
+        return 0.0;
+    }
+
+    // Coarse search in half degrees, then refine around the best coarse angle
+    let best_in = |angles: Vec<f32>| {
+        angles.into_iter()
+            .map(|angle| (angle, projection_sharpness(&dark, angle, height)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0.0, |(angle, _)| angle)
+    };
+    let steps = (MAX_SKEW_DEGREES * 2.0) as i32;
+    let coarse = best_in((-steps..=steps).map(|i| i as f32 * 0.5).collect());
+    best_in((-10..=10).map(|i| coarse + i as f32 * 0.05).collect())
+}
+
+/// Straighten a page whose content is skewed by a degree or two.
+/// Returns the straightened image and the transform applied, or None when the page is already level.
+pub fn deskew(img: &RgbImage) -> (RgbImage, Option<Deskew>) {
+    let angle = estimate_skew(img);
+    if angle.abs() < MIN_SKEW_DEGREES {
+        return (img.clone(), None);
+    }
+    println!("[DEBUG] Deskewing page by {:.2} degrees", angle);
+
+    let transform = Deskew { angle, width: img.width(), height: img.height() };
+    let straightened = RgbImage::from_fn(img.width(), img.height(), |x, y| {
+        let (sx, sy) = transform.point_to_original(x as f32 + 0.5, y as f32 + 0.5);
+        if sx >= 0.0 && sy >= 0.0 && (sx as u32) < img.width() && (sy as u32) < img.height() {
+            *img.get_pixel(sx as u32, sy as u32)
+        } else {
+            Rgb([255, 255, 255])
+        }
+    });
+    (straightened, Some(transform))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dark strokes on a page whose left half is shaded grey
+    fn shaded_page() -> GrayImage {
+        GrayImage::from_fn(200, 100, |x, y| {
+            let stroke = (30..33).contains(&x) || (150..153).contains(&x);
+            if stroke && (20..80).contains(&y) {
+                Luma([30])
+            } else if x < 100 {
+                Luma([120])
+            } else {
+                Luma([240])
+            }
+        })
+    }
+
+    #[test]
+    fn otsu_splits_two_tones() {
+        let gray = GrayImage::from_fn(10, 10, |x, _| Luma([if x < 5 { 40 } else { 200 }]));
+        let threshold = otsu_threshold(&gray);
+        assert!((40..200).contains(&threshold), "{}", threshold);
+        let binary = binarize(&gray, Binarization::Otsu);
+        assert_eq!((binary.get_pixel(0, 0)[0], binary.get_pixel(9, 0)[0]), (0, 255));
+    }
+
+    #[test]
+    fn sauvola_keeps_shading_white_where_otsu_blackens_it() {
+        let gray = shaded_page();
+        let otsu = binarize(&gray, Binarization::Otsu);
+        let sauvola = binarize(&gray, Binarization::Sauvola);
+        assert_eq!(otsu.get_pixel(70, 50)[0], 0);
+        assert_eq!(sauvola.get_pixel(70, 50)[0], 255);
+        assert_eq!(sauvola.get_pixel(170, 50)[0], 255);
+        assert_eq!(sauvola.get_pixel(31, 50)[0], 0);
+        assert_eq!(sauvola.get_pixel(151, 50)[0], 0);
+    }
+
+    /// A white page with thick lines running `angle` degrees clockwise of level
+    fn skewed_lines(angle: f32) -> RgbImage {
+        let (width, height) = (800, 600);
+        let slope = angle.to_radians().tan();
+        RgbImage::from_fn(width, height, |x, y| {
+            let level_y = y as f32 - (x as f32 - width as f32 / 2.0) * slope;
+            let on_line = (level_y - 100.0).rem_euclid(60.0) < 3.0 && (100.0..500.0).contains(&level_y);
+            if on_line { Rgb([0, 0, 0]) } else { Rgb([255, 255, 255]) }
+        })
+    }
+
+    #[test]
+    fn skew_is_found_and_boxes_map_back_onto_the_lines() {
+        let page = skewed_lines(1.5);
+        assert!((estimate_skew(&page) - 1.5).abs() <= 0.1, "{}", estimate_skew(&page));
+
+        let (straightened, transform) = deskew(&page);
+        let transform = transform.unwrap();
+        let dark = |img: &RgbImage, [x, y]: [f32; 2]| img.get_pixel((x * 800.0) as u32, (y * 600.0) as u32)[0] < 128;
+
+        // A word box on a line near the right edge of the straightened page
+        let (x, y) = (700.0, 221.5);
+        assert!(dark(&straightened, [x / 800.0, y / 600.0]));
+        let bbox = [(x - 20.0) / 800.0, (y - 4.0) / 600.0, (x + 20.0) / 800.0, (y + 4.0) / 600.0];
+        let mapped = transform.bbox_to_original(bbox);
+        assert!(dark(&page, [(mapped[0] + mapped[2]) / 2.0, (mapped[1] + mapped[3]) / 2.0]), "{:?}", mapped);
+        // The tilted line rises across the box, so the mapped box is taller than the original
+        assert!(mapped[3] - mapped[1] > bbox[3] - bbox[1]);
+
+        let quad = [[bbox[0], bbox[1]], [bbox[2], bbox[1]], [bbox[2], bbox[3]], [bbox[0], bbox[3]]];
+        let corners = transform.quad_to_original(quad);
+        let center = [corners.iter().map(|c| c[0]).sum::<f32>() / 4.0, corners.iter().map(|c| c[1]).sum::<f32>() / 4.0];
+        assert!(dark(&page, center), "{:?}", corners);
+        assert!(corners[1][1] > corners[0][1], "the top edge should slope down to the right: {:?}", corners);
+    }
+
+    #[test]
+    fn level_pages_are_left_alone() {
+        let (_, transform) = deskew(&skewed_lines(0.0));
+        assert_eq!(transform, None);
+    }
+}
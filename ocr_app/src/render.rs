@@ -0,0 +1,148 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::tiling::{DEFAULT_TILE_OVERLAP, MIN_TILE_SIZE};
+use crate::Binarization;
+
+/// Lowest render resolution accepted; below it labels are too small to read
+pub const MIN_DPI: f32 = 72.0;
+
+/// Highest render resolution accepted; above it a single page takes gigabytes
+pub const MAX_DPI: f32 = 1200.0;
+
+/// Smallest pixel budget per page accepted, about a letter page at 100 DPI
+pub const MIN_MAX_PIXELS: u64 = 1_000_000;
+
+/// How PDF pages are rendered before OCR
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub dpi: f32,                     // Render resolution, 300 for best quality
+    pub pages: Vec<(usize, usize)>,   // Inclusive 1-based page ranges to OCR; empty means every page
+    pub max_pixels: Option<u64>,      // Pixel budget per page; the DPI is lowered to stay within it
+    pub binarization: Binarization,
+    pub deskew: bool,                 // Straighten scans skewed by a degree or two before OCR
+    pub tile_size: Option<u32>,       // OCR pages larger than this many pixels on a side in overlapping tiles
+    pub tile_overlap: u32,            // Pixels shared by neighbouring tiles
+    pub text_layer: bool,             // Take words from the PDF's own text objects where it has them
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            dpi: 300.0,
+            pages: Vec::new(),
+            max_pixels: None,
+            binarization: Binarization::default(),
+            deskew: true,
+            tile_size: None,
+            tile_overlap: DEFAULT_TILE_OVERLAP,
+            text_layer: true,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Check that the options stay within the limits a page can be rendered and OCR'd with
+    pub fn validate(&self) -> Result<()> {
+        if !(MIN_DPI..=MAX_DPI).contains(&self.dpi) {
+            bail!("DPI must be between {} and {}, got {}", MIN_DPI, MAX_DPI, self.dpi);
+        }
+        if let Some(max_pixels) = self.max_pixels.filter(|&pixels| pixels < MIN_MAX_PIXELS) {
+            bail!("max_pixels must be at least {}, got {}", MIN_MAX_PIXELS, max_pixels);
+        }
+        if let Some(tile_size) = self.tile_size {
+            if tile_size < MIN_TILE_SIZE {
+                bail!("tile_size must be at least {}, got {}", MIN_TILE_SIZE, tile_size);
+            }
+            if self.tile_overlap >= tile_size / 2 {
+                bail!("tile_overlap must be less than half the tile size ({}), got {}", tile_size / 2, self.tile_overlap);
+            }
+        }
+        Ok(())
+    }
+
+    /// Zero-based indexes of the pages to render, in document order
+    pub fn selected_pages(&self, page_count: usize) -> Result<Vec<usize>> {
+        if self.pages.is_empty() {
+            return Ok((0..page_count).collect());
+        }
+
+        let mut selected = Vec::new();
+        for &(first, last) in &self.pages {
+            if last > page_count {
+                return Err(anyhow!("Page {} is out of range (the PDF has {} pages)", last, page_count));
+            }
+            selected.extend((first - 1)..last);
+        }
+        selected.sort_unstable();
+        selected.dedup();
+        Ok(selected)
+    }
+
+    /// DPI to render a page of the given size in points at, lowered if it would exceed the pixel budget
+    pub fn dpi_for(&self, width_points: f32, height_points: f32) -> f32 {
+        let Some(max_pixels) = self.max_pixels else {
+            return self.dpi;
+        };
+
+        let scale = self.dpi / 72.0;
+        let pixels = (width_points * scale) as f64 * (height_points * scale) as f64;
+        if pixels <= max_pixels as f64 {
+            return self.dpi;
+        }
+
+        let dpi = self.dpi * (max_pixels as f64 / pixels).sqrt() as f32;
+        println!("[DEBUG] Lowering DPI from {} to {:.0} to stay within {} pixels", self.dpi, dpi, max_pixels);
+        dpi
+    }
+}
+
+/// Parse a page selection such as "1-3,7" into inclusive 1-based ranges
+pub fn parse_page_ranges(spec: &str) -> Result<Vec<(usize, usize)>> {
+    let mut ranges = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (first.trim(), last.trim()),
+            None => (part, part),
+        };
+        let parse = |page: &str| {
+            page.parse::<usize>()
+                .ok()
+                .filter(|&page| page > 0)
+                .ok_or_else(|| anyhow!("Invalid page number '{}' in page selection '{}'", page, spec))
+        };
+        let (first, last) = (parse(first)?, parse(last)?);
+        if last < first {
+            return Err(anyhow!("Invalid page range '{}': {} comes after {}", part, first, last));
+        }
+        ranges.push((first, last));
+    }
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_bounds_dpi_and_pixel_budget() {
+        assert!(RenderOptions::default().validate().is_ok());
+        for dpi in [0.0, 71.0, 1201.0, 100_000.0, f32::NAN] {
+            assert!(RenderOptions { dpi, ..RenderOptions::default() }.validate().is_err(), "{}", dpi);
+        }
+        assert!(RenderOptions { max_pixels: Some(10), ..RenderOptions::default() }.validate().is_err());
+        assert!(RenderOptions { max_pixels: Some(MIN_MAX_PIXELS), ..RenderOptions::default() }.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_bounds_tiles() {
+        let tiled = |tile_size, tile_overlap| RenderOptions { tile_size: Some(tile_size), tile_overlap, ..RenderOptions::default() };
+        assert!(tiled(2048, 160).validate().is_ok());
+        assert!(tiled(256, 100).validate().is_ok());
+        assert!(tiled(1, 0).validate().is_err());
+        assert!(tiled(255, 0).validate().is_err());
+        assert!(tiled(512, 256).validate().is_err());
+        // Without tiling the overlap is unused
+        assert!(RenderOptions { tile_overlap: 10_000, ..RenderOptions::default() }.validate().is_ok());
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use image::{Rgb, RgbImage};
+use ocrs::{ImageSource, OcrEngine};
+use rten_imageproc::{Rect, RotatedRect, Vec2};
+
+use crate::orientation::{readability, LabelMatcher};
+
+/// Words tilted more than this are cropped and straightened before recognition, in degrees
+const ROTATED_WORD_DEGREES: f32 = 10.0;
+
+/// A word box this much taller than wide is treated as text running vertically
+const VERTICAL_ASPECT: f32 = 1.2;
+
+/// White margin around a straightened crop, in pixels
+const CROP_PADDING: u32 = 4;
+
+/// The rect with its up axis as close to the page's up as possible
+fn upright(rect: &RotatedRect) -> RotatedRect {
+    rect.orient_towards(Vec2::from_yx(-1., 0.))
+}
+
+/// Tilt of a word box from upright in degrees, clockwise positive, within [-45, 45]
+pub fn tilt(rect: &RotatedRect) -> f32 {
+    let up = upright(rect).up_axis();
+    up.x.atan2(-up.y).to_degrees()
+}
+
+/// Whether a word is tilted or runs vertically, so line recognition would misread it
+pub fn is_rotated(rect: &RotatedRect) -> bool {
+    let oriented = upright(rect);
+    tilt(rect).abs() > ROTATED_WORD_DEGREES || oriented.height() > oriented.width() * VERTICAL_ASPECT
+}
+
+/// Crop a word out of the page and turn it upright. Each of `quarter_turns` turns the
+/// reading direction a further 90 degrees counterclockwise, for text running along the box's height.
+pub fn crop_upright(img: &RgbImage, rect: &RotatedRect, quarter_turns: u32) -> RgbImage {
+    let oriented = upright(rect);
+
+    // Reading direction and the direction "down" the text, in page coordinates
+    let up = oriented.up_axis();
+    let (mut along, mut down) = (Vec2::from_xy(-up.y, up.x), Vec2::from_xy(-up.x, -up.y));
+    let (mut width, mut height) = (oriented.width(), oriented.height());
+    for _ in 0..quarter_turns % 4 {
+        (along, down) = (Vec2::from_xy(-down.x, -down.y), along);
+        (width, height) = (height, width);
+    }
+
+    let center = oriented.center();
+    let crop_width = width.ceil() as u32 + 2 * CROP_PADDING;
+    let crop_height = height.ceil() as u32 + 2 * CROP_PADDING;
+    RgbImage::from_fn(crop_width, crop_height, |u, v| {
+        let u = u as f32 + 0.5 - crop_width as f32 / 2.0;
+        let v = v as f32 + 0.5 - crop_height as f32 / 2.0;
+        let x = center.x + u * along.x + v * down.x;
+        let y = center.y + u * along.y + v * down.y;
+        if x >= 0.0 && y >= 0.0 && (x as u32) < img.width() && (y as u32) < img.height() {
+            *img.get_pixel(x as u32, y as u32)
+        } else {
+            Rgb([255, 255, 255])
+        }
+    })
+}
+
+/// Recognize a single tilted or vertical word from a straightened crop.
+/// Vertical text may read bottom-to-top or top-to-bottom, so both turns are tried
+/// along with no turn at all (a tall box may just be a short numeral), and the
+/// reading that best fits the label grammar is kept.
+pub fn recognize_word(engine: &OcrEngine, img: &RgbImage, rect: &RotatedRect, matcher: &LabelMatcher) -> Result<Option<String>> {
+    let oriented = upright(rect);
+    let turns: &[u32] = if oriented.height() > oriented.width() * VERTICAL_ASPECT { &[0, 1, 3] } else { &[0] };
+
+    let mut best: Option<(f32, String)> = None;
+    for &quarter_turns in turns {
+        let crop = crop_upright(img, rect, quarter_turns);
+        let img_source = ImageSource::from_bytes(crop.as_raw(), crop.dimensions())
+            .map_err(|e| anyhow::anyhow!("Failed to create image source: {}", e))?;
+        let ocr_input = engine.prepare_input(img_source)
+            .map_err(|e| anyhow::anyhow!("Failed to prepare OCR input: {}", e))?;
+
+        // The whole crop is one word on one line
+        let (width, height) = crop.dimensions();
+        let line = vec![RotatedRect::from_rect(Rect::from_tlbr(0., 0., height as f32, width as f32))];
+        let line_texts = engine.recognize_text(&ocr_input, &[line])
+            .map_err(|e| anyhow::anyhow!("Failed to recognize text: {}", e))?;
+
+        let score = readability(&line_texts, matcher);
+        if let Some(text) = line_texts.into_iter().flatten().next().map(|line| line.to_string()) {
+            if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+                best = Some((score, text));
+            }
+        }
+    }
+
+    Ok(best.map(|(_, text)| text))
+}
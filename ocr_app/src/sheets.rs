@@ -0,0 +1,179 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use image::{imageops, DynamicImage, GrayImage, RgbImage};
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::ColorType;
+
+use crate::{preprocess, RenderOptions};
+
+/// Whether a file starts with a TIFF header, in either byte order
+fn is_tiff(path: &Path) -> Result<bool> {
+    let mut magic = [0u8; 4];
+    let read = File::open(path)
+        .context("Failed to open drawing file")?
+        .read(&mut magic)?;
+    Ok(read == 4 && (magic == *b"II*\0" || magic == *b"MM\0*"))
+}
+
+fn tiff_decoder(path: &Path) -> Result<Decoder<BufReader<File>>> {
+    let file = File::open(path).context("Failed to open TIFF file")?;
+    Decoder::new(BufReader::new(file)).context("Failed to read TIFF header")
+}
+
+/// Number of sheets in a drawing file: every page of a TIFF, or one for PNG and JPEG
+pub fn sheet_count(path: impl AsRef<Path>) -> Result<usize> {
+    let path = path.as_ref();
+    if !is_tiff(path)? {
+        return Ok(1);
+    }
+
+    let mut decoder = tiff_decoder(path)?;
+    let mut count = 1;
+    while decoder.more_images() {
+        decoder.next_image().context("Failed to read TIFF page directory")?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Decode the current page of a TIFF to grayscale. Bilevel pages, such as the CCITT G4 sheets
+/// in file wrappers, come packed eight pixels to a byte with 0 as black.
+fn decode_tiff_page(decoder: &mut Decoder<BufReader<File>>) -> Result<GrayImage> {
+    let (width, height) = decoder.dimensions()?;
+    let color_type = decoder.colortype()?;
+    let data = decoder.read_image().context("Failed to decode TIFF page")?;
+    let unsupported = || anyhow!("Unsupported TIFF color type {:?}", color_type);
+
+    let image = match (color_type, data) {
+        (ColorType::Gray(1), DecodingResult::U8(packed)) => {
+            let row_bytes = width.div_ceil(8) as usize;
+            GrayImage::from_fn(width, height, |x, y| {
+                let byte = packed[y as usize * row_bytes + x as usize / 8];
+                let bit = (byte >> (7 - x % 8)) & 1;
+                image::Luma([bit * 255])
+            })
+        }
+        (ColorType::Gray(8), DecodingResult::U8(data)) => {
+            GrayImage::from_raw(width, height, data).ok_or_else(unsupported)?
+        }
+        (ColorType::Gray(16), DecodingResult::U16(data)) => {
+            GrayImage::from_raw(width, height, data.iter().map(|&v| (v >> 8) as u8).collect()).ok_or_else(unsupported)?
+        }
+        (ColorType::GrayA(8), DecodingResult::U8(data)) => {
+            DynamicImage::ImageLumaA8(image::GrayAlphaImage::from_raw(width, height, data).ok_or_else(unsupported)?).to_luma8()
+        }
+        (ColorType::RGB(8), DecodingResult::U8(data)) => {
+            DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, data).ok_or_else(unsupported)?).to_luma8()
+        }
+        (ColorType::RGBA(8), DecodingResult::U8(data)) => {
+            DynamicImage::ImageRgba8(image::RgbaImage::from_raw(width, height, data).ok_or_else(unsupported)?).to_luma8()
+        }
+        _ => return Err(unsupported()),
+    };
+    Ok(image)
+}
+
+/// Load one sheet of a drawing file in grayscale, by its zero-based index
+pub fn load_sheet(path: impl AsRef<Path>, index: usize) -> Result<GrayImage> {
+    let path = path.as_ref();
+    if !is_tiff(path)? {
+        let image = image::io::Reader::open(path)
+            .context("Failed to open drawing file")?
+            .with_guessed_format()?
+            .decode()
+            .context("Failed to decode drawing image")?;
+        return Ok(image.to_luma8());
+    }
+
+    let mut decoder = tiff_decoder(path)?;
+    for _ in 0..index {
+        decoder.next_image().context("Failed to find TIFF page")?;
+    }
+    decode_tiff_page(&mut decoder)
+}
+
+/// Get a scanned sheet ready for OCR as `pdf_page_to_image` does for rendered pages.
+/// Sheets have a fixed resolution, so the DPI is not used, but sheets over the pixel budget are scaled down.
+pub fn prepare_sheet(gray: GrayImage, options: &RenderOptions) -> RgbImage {
+    let (width, height) = gray.dimensions();
+    let pixels = width as u64 * height as u64;
+    let gray = match options.max_pixels {
+        Some(max_pixels) if pixels > max_pixels => {
+            let scale = (max_pixels as f64 / pixels as f64).sqrt();
+            let (new_width, new_height) = ((width as f64 * scale) as u32, (height as f64 * scale) as u32);
+            println!("[DEBUG] Scaling sheet from {}x{} to {}x{} to stay within {} pixels", width, height, new_width, new_height, max_pixels);
+            imageops::resize(&gray, new_width.max(1), new_height.max(1), imageops::FilterType::Triangle)
+        }
+        _ => gray,
+    };
+
+    let binarized = preprocess::binarize(&gray, options.binarization);
+    DynamicImage::ImageLuma8(binarized).to_rgb8()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocess::Binarization;
+    use tiff::encoder::{colortype, TiffEncoder};
+    use tiff::tags::{PhotometricInterpretation, SampleFormat};
+
+    /// One bit per pixel with 0 as black, as fax-style sheets are stored
+    struct Bilevel;
+
+    impl colortype::ColorType for Bilevel {
+        type Inner = u8;
+        const TIFF_VALUE: PhotometricInterpretation = PhotometricInterpretation::BlackIsZero;
+        const BITS_PER_SAMPLE: &'static [u16] = &[1];
+        const SAMPLE_FORMAT: &'static [SampleFormat] = &[SampleFormat::Uint];
+
+        fn horizontal_predict(_row: &[u8], _result: &mut Vec<u8>) {}
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("sheets_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn bilevel_tiff_pages_are_unpacked() {
+        let path = temp_path("pages.tif");
+        let mut encoder = TiffEncoder::new(File::create(&path).unwrap()).unwrap();
+        // The encoder counts a byte per pixel, so the packed rows are padded out to that length
+        let mut packed = vec![0xF0, 0x0F, 0x00, 0xFF];
+        packed.resize(16 * 2, 0);
+        encoder.write_image::<Bilevel>(16, 2, &packed).unwrap();
+        packed[..4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        encoder.write_image::<Bilevel>(16, 2, &packed).unwrap();
+        drop(encoder);
+
+        assert_eq!(sheet_count(&path).unwrap(), 2);
+        let first = load_sheet(&path, 0).unwrap();
+        assert_eq!(first.dimensions(), (16, 2));
+        let row = |y| (0..16).map(|x| first.get_pixel(x, y)[0]).collect::<Vec<u8>>();
+        assert_eq!(row(0), [[255; 4], [0; 4], [0; 4], [255; 4]].concat());
+        assert_eq!(row(1), [[0; 8], [255; 8]].concat());
+        assert!(load_sheet(&path, 1).unwrap().pixels().all(|p| p[0] == 255));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn images_are_one_sheet_scaled_to_the_pixel_budget() {
+        let path = temp_path("sheet.png");
+        RgbImage::from_pixel(400, 300, image::Rgb([255, 255, 255])).save(&path).unwrap();
+
+        assert_eq!(sheet_count(&path).unwrap(), 1);
+        let gray = load_sheet(&path, 0).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(gray.dimensions(), (400, 300));
+
+        let options = RenderOptions { max_pixels: Some(30_000), binarization: Binarization::None, ..RenderOptions::default() };
+        let prepared = prepare_sheet(gray.clone(), &options);
+        assert_eq!(prepared.dimensions(), (200, 150));
+        assert_eq!(prepared.get_pixel(10, 10), &image::Rgb([255, 255, 255]));
+        let unlimited = RenderOptions { max_pixels: None, ..options };
+        assert_eq!(prepare_sheet(gray, &unlimited).dimensions(), (400, 300));
+    }
+}
@@ -0,0 +1,634 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use mupdf::{Document, TextPageOptions};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::docx;
+
+/// How much of a specification file is read to tell its format
+const SNIFF_LENGTH: u64 = 8192;
+
+/// Signature of OLE compound files, the container of legacy Word .doc and WordPerfect exports
+const OLE_MAGIC: [u8; 4] = [0xD0, 0xCF, 0x11, 0xE0];
+
+/// RTF destinations that hold no document text, such as font tables, pictures and field codes
+const RTF_SKIPPED_DESTINATIONS: [&str; 28] = [
+    "fonttbl", "colortbl", "stylesheet", "info", "pict", "object", "fldinst", "themedata",
+    "colorschememapping", "latentstyles", "datastore", "listtable", "listoverridetable",
+    "rsidtbl", "generator", "filetbl", "revtbl", "xmlnstbl", "bkmkstart", "bkmkend",
+    "header", "headerl", "headerr", "headerf", "footer", "footerl", "footerr", "footerf",
+];
+
+/// Whether the start of a file looks like binary data rather than text: control characters
+/// other than whitespace. Text need not be UTF-8, since older files are often Windows-1252.
+fn is_binary(sample: &[u8]) -> bool {
+    sample.iter().any(|&b| b.is_ascii_control() && !b.is_ascii_whitespace())
+}
+
+/// Part of the document a paragraph was found in
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ParagraphOrigin {
+    Body,
+    TableCell,
+    TextBox,
+    Footnote,
+    Header,
+    Footer,
+    Comment,  // Only read when tracked changes are shown with markup
+}
+
+/// Which version of a document with tracked changes to read
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionView {
+    /// The text as it will be filed: insertions kept, deletions dropped
+    #[default]
+    Final,
+    /// The text before any tracked change: deletions kept, insertions dropped
+    Original,
+    /// Both versions, with insertions marked "[+ ... +]", deletions "[- ... -]", and comments added
+    Markup,
+}
+
+impl std::str::FromStr for RevisionView {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "final" => Ok(Self::Final),
+            "original" => Ok(Self::Original),
+            "markup" | "both" => Ok(Self::Markup),
+            _ => Err(anyhow::anyhow!("Unknown revision view: {} (expected final, original or markup)", s)),
+        }
+    }
+}
+
+/// A paragraph of the specification and where it was found
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecParagraph {
+    pub text: String,
+    pub origin: ParagraphOrigin,
+}
+
+impl SpecParagraph {
+    pub fn new(text: impl Into<String>, origin: ParagraphOrigin) -> Self {
+        SpecParagraph { text: text.into(), origin }
+    }
+}
+
+/// A specification file and the format its paragraphs are read from
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecSource {
+    Docx(PathBuf),
+    Odt(PathBuf),
+    Rtf(PathBuf),
+    Text(PathBuf),  // Plain text or Markdown
+    Pdf(PathBuf),   // Issued patents and published applications, read from the text layer
+}
+
+impl SpecSource {
+    /// Tell the format of a specification file from its content, since uploads carry no reliable name.
+    /// Anything else is read as plain text, unless it is a legacy .doc or other binary file.
+    pub fn detect(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut magic = Vec::new();
+        File::open(&path)
+            .context("Failed to open specification file")?
+            .take(SNIFF_LENGTH)
+            .read_to_end(&mut magic)?;
+        let magic = magic.as_slice();
+
+        if magic.starts_with(b"%PDF") {
+            return Ok(SpecSource::Pdf(path));
+        }
+        if magic.starts_with(b"{\\rtf") {
+            return Ok(SpecSource::Rtf(path));
+        }
+        if magic.starts_with(b"PK") {
+            // DOCX and ODT are both zip archives, told apart by their main part
+            let archive = zip::ZipArchive::new(File::open(&path)?)
+                .context("Failed to read specification archive")?;
+            if archive.index_for_name("word/document.xml").is_some() {
+                return Ok(SpecSource::Docx(path));
+            }
+            if archive.index_for_name("content.xml").is_some() {
+                return Ok(SpecSource::Odt(path));
+            }
+            bail!("Unsupported specification archive: expected DOCX or ODT");
+        }
+        if magic.starts_with(&OLE_MAGIC) {
+            bail!("Unsupported specification format: legacy Word .doc files must be saved as DOCX first");
+        }
+        if is_binary(magic) {
+            bail!("Unsupported specification format: expected PDF, RTF, DOCX, ODT or plain text");
+        }
+        Ok(SpecSource::Text(path))
+    }
+
+    /// Name of the format, for messages
+    pub fn format_name(&self) -> &'static str {
+        match self {
+            SpecSource::Docx(_) => "DOCX",
+            SpecSource::Odt(_) => "ODT",
+            SpecSource::Rtf(_) => "RTF",
+            SpecSource::Text(_) => "text",
+            SpecSource::Pdf(_) => "PDF",
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            SpecSource::Docx(path) | SpecSource::Odt(path) | SpecSource::Rtf(path) | SpecSource::Text(path) | SpecSource::Pdf(path) => path,
+        }
+    }
+
+    /// Read the paragraphs of the specification in reading order, trimmed and without empty ones.
+    /// Notes and text boxes follow the paragraph they are anchored in. Tracked changes are read
+    /// as `revisions` asks in DOCX; other formats are read in their final form.
+    pub fn paragraphs(&self, revisions: RevisionView) -> Result<Vec<SpecParagraph>> {
+        println!("[DEBUG] Reading {} specification: {}", self.format_name(), self.path().display());
+        let paragraphs = match self {
+            SpecSource::Docx(path) => docx::paragraphs(path, revisions)?,
+            SpecSource::Odt(path) => odt_paragraphs(path)?,
+            SpecSource::Rtf(path) => rtf_paragraphs(path)?,
+            // Plain text and PDF text layers only have body text
+            SpecSource::Text(path) => body_paragraphs(text_paragraphs(path)?),
+            SpecSource::Pdf(path) => body_paragraphs(pdf_paragraphs(path)?),
+        };
+
+        Ok(paragraphs.into_iter()
+            .map(|paragraph| SpecParagraph::new(paragraph.text.trim(), paragraph.origin))
+            .filter(|paragraph| !paragraph.text.is_empty())
+            .collect())
+    }
+}
+
+fn body_paragraphs(texts: Vec<String>) -> Vec<SpecParagraph> {
+    texts.into_iter().map(|text| SpecParagraph::new(text, ParagraphOrigin::Body)).collect()
+}
+
+/// Paragraphs and headings of an ODT's content.xml. Footnotes and text boxes become paragraphs of
+/// their own after the paragraph they are anchored in; note markers, comments and deleted text are left out.
+fn odt_paragraphs(path: &Path) -> Result<Vec<SpecParagraph>> {
+    let mut archive = zip::ZipArchive::new(File::open(path).context("Failed to open ODT file")?)
+        .context("Failed to read ODT archive")?;
+    let mut content = String::new();
+    archive.by_name("content.xml")
+        .context("ODT file has no content.xml")?
+        .read_to_string(&mut content)
+        .context("Failed to read ODT content")?;
+
+    let mut reader = Reader::from_str(&content);
+    let mut paragraphs = Vec::new();
+    let mut open: Vec<SpecParagraph> = Vec::new();  // Paragraphs being read, innermost last
+    let mut anchored = Vec::new();                  // Note and text box paragraphs waiting for their host paragraph to end
+    let mut skipped_depth = 0;                      // Depth inside an element whose text is left out
+    let mut containers: Vec<ParagraphOrigin> = Vec::new();  // Table cells, notes and text boxes being read
+
+    loop {
+        match reader.read_event().context("Failed to parse ODT content")? {
+            Event::Start(_) if skipped_depth > 0 => skipped_depth += 1,
+            Event::End(_) if skipped_depth > 0 => skipped_depth -= 1,
+            _ if skipped_depth > 0 => {}
+            Event::Start(e) => match e.name().as_ref() {
+                b"text:p" | b"text:h" => {
+                    let origin = containers.last().copied().unwrap_or(ParagraphOrigin::Body);
+                    open.push(SpecParagraph::new("", origin));
+                }
+                b"table:table-cell" => containers.push(ParagraphOrigin::TableCell),
+                b"text:note-body" => containers.push(ParagraphOrigin::Footnote),
+                b"draw:text-box" => containers.push(ParagraphOrigin::TextBox),
+                b"text:note-citation" | b"office:annotation" | b"text:tracked-changes" => skipped_depth = 1,
+                _ => {}
+            },
+            Event::End(e) => match e.name().as_ref() {
+                b"text:p" | b"text:h" => {
+                    let Some(paragraph) = open.pop() else { continue };
+                    if open.is_empty() {
+                        paragraphs.push(paragraph);
+                        paragraphs.append(&mut anchored);
+                    } else {
+                        anchored.push(paragraph);
+                    }
+                }
+                b"table:table-cell" | b"text:note-body" | b"draw:text-box" => {
+                    containers.pop();
+                }
+                _ => {}
+            },
+            Event::Empty(e) => {
+                let Some(paragraph) = open.last_mut() else { continue };
+                match e.name().as_ref() {
+                    // Runs of spaces are stored as a count
+                    b"text:s" => {
+                        let count = match e.try_get_attribute("text:c")? {
+                            Some(count) => std::str::from_utf8(&count.value).ok().and_then(|c| c.parse().ok()).unwrap_or(1),
+                            None => 1,
+                        };
+                        paragraph.text.extend(std::iter::repeat_n(' ', count));
+                    }
+                    b"text:tab" | b"text:line-break" => paragraph.text.push(' '),
+                    _ => {}
+                }
+            }
+            Event::Text(e) => {
+                if let Some(paragraph) = open.last_mut() {
+                    paragraph.text.push_str(&e.decode()?);
+                }
+            }
+            Event::GeneralRef(e) => {
+                if let Some(paragraph) = open.last_mut() {
+                    if let Some(c) = e.resolve_char_ref()? {
+                        paragraph.text.push(c);
+                    } else if let Some(entity) = quick_xml::escape::resolve_predefined_entity(&e.decode()?) {
+                        paragraph.text.push_str(entity);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    paragraphs.append(&mut anchored);
+    Ok(paragraphs)
+}
+
+/// Character for a byte of Windows-1252 text, which RTF writes as \'hh escapes and older text files use throughout
+fn cp1252_char(byte: u8) -> char {
+    match byte {
+        0x80 => '€',
+        0x85 => '…',
+        0x91 => '‘',
+        0x92 => '’',
+        0x93 => '“',
+        0x94 => '”',
+        0x95 => '•',
+        0x96 => '–',
+        0x97 => '—',
+        0x99 => '™',
+        _ => byte as char,
+    }
+}
+
+/// State of an RTF group, inherited by the groups nested in it
+#[derive(Clone, Copy, Default)]
+struct RtfGroup {
+    skipped: bool,    // Inside a destination without document text
+    footnote: bool,   // Inside a footnote, whose text becomes paragraphs of its own
+    deleted: bool,    // Text marked as a tracked deletion
+    unicode_skip: usize,  // Fallback characters that follow each \u character
+}
+
+/// Paragraphs of an RTF document, ended by \par and table cells. Footnotes become paragraphs of
+/// their own after the paragraph that cites them; headers, footers, other destinations and
+/// tracked deletions are left out.
+fn rtf_paragraphs(path: &Path) -> Result<Vec<SpecParagraph>> {
+    let content = std::fs::read(path)
+        .context("Failed to read RTF file")?;
+
+    let mut text_out = RtfText::default();
+    let mut groups = vec![RtfGroup { unicode_skip: 1, ..Default::default() }];
+    let mut fallback = 0;  // Fallback characters still to skip after a \u character
+    let mut high_surrogate = None;
+
+    let mut i = 0;
+    while i < content.len() {
+        let group = *groups.last().unwrap();
+        let byte = content[i];
+        i += 1;
+
+        // Text of the group goes to the footnote or paragraph it belongs to
+        let mut text = None;
+        match byte {
+            b'{' => groups.push(group),
+            b'}' => {
+                groups.pop();
+                if groups.is_empty() {
+                    break;
+                }
+                if group.footnote && !groups.last().unwrap().footnote {
+                    text_out.end_note();
+                }
+            }
+            b'\r' | b'\n' => {}
+            b'\\' => {
+                let Some(&next) = content.get(i) else { break };
+                if !next.is_ascii_alphabetic() {
+                    i += 1;
+                    match next {
+                        b'\'' => {
+                            let hex = content.get(i..i + 2).and_then(|hex| std::str::from_utf8(hex).ok());
+                            i += 2;
+                            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                                text = Some(cp1252_char(byte));
+                            }
+                        }
+                        b'*' => groups.last_mut().unwrap().skipped = true,
+                        b'~' => text = Some(' '),
+                        b'_' => text = Some('-'),
+                        b'\\' | b'{' | b'}' => text = Some(next as char),
+                        b'\r' | b'\n' => text = Some('\n'),
+                        _ => {}
+                    }
+                } else {
+                    // A control word, an optional signed parameter, and an optional space delimiter
+                    let start = i;
+                    while content.get(i).is_some_and(u8::is_ascii_alphabetic) {
+                        i += 1;
+                    }
+                    let word = std::str::from_utf8(&content[start..i]).unwrap_or_default();
+                    let number_start = i;
+                    if content.get(i) == Some(&b'-') {
+                        i += 1;
+                    }
+                    while content.get(i).is_some_and(u8::is_ascii_digit) {
+                        i += 1;
+                    }
+                    let parameter: Option<i32> = std::str::from_utf8(&content[number_start..i]).ok().and_then(|n| n.parse().ok());
+                    if content.get(i) == Some(&b' ') {
+                        i += 1;
+                    }
+
+                    match word {
+                        "par" | "cell" | "row" | "sect" => text = Some('\n'),
+                        "line" | "tab" | "emspace" | "enspace" => text = Some(' '),
+                        "emdash" => text = Some('—'),
+                        "endash" => text = Some('–'),
+                        "lquote" => text = Some('‘'),
+                        "rquote" => text = Some('’'),
+                        "ldblquote" => text = Some('“'),
+                        "rdblquote" => text = Some('”'),
+                        "uc" => groups.last_mut().unwrap().unicode_skip = parameter.unwrap_or(1).max(0) as usize,
+                        "u" => {
+                            // Code points over 32767 are written as negative numbers
+                            let unit = parameter.unwrap_or(0).rem_euclid(65536) as u32;
+                            if (0xD800..0xDC00).contains(&unit) {
+                                high_surrogate = Some(unit);
+                            } else if let Some(high) = high_surrogate.take() {
+                                text = char::from_u32(0x10000 + ((high - 0xD800) << 10) + (unit.wrapping_sub(0xDC00) & 0x3FF));
+                            } else {
+                                text = char::from_u32(unit);
+                            }
+                            if !group.skipped && !group.deleted {
+                                if let Some(c) = text.take() {
+                                    text_out.push(c, group.footnote);
+                                }
+                            }
+                            fallback = group.unicode_skip;
+                            continue;
+                        }
+                        "footnote" => groups.last_mut().unwrap().footnote = true,
+                        "deleted" => groups.last_mut().unwrap().deleted = parameter != Some(0),
+                        "plain" => groups.last_mut().unwrap().deleted = false,
+                        "intbl" if !group.footnote => text_out.in_table = true,
+                        "pard" if !group.footnote => text_out.in_table = false,
+                        _ if RTF_SKIPPED_DESTINATIONS.contains(&word) => groups.last_mut().unwrap().skipped = true,
+                        _ => {}
+                    }
+                }
+            }
+            _ => text = Some(cp1252_char(byte)),
+        }
+
+        // Characters standing in for a \u character in older readers are dropped
+        let Some(c) = text else { continue };
+        if fallback > 0 && c != '\n' {
+            fallback -= 1;
+            continue;
+        }
+        if !group.skipped && (!group.deleted || c == '\n') {
+            text_out.push(c, group.footnote);
+        }
+    }
+
+    text_out.end_paragraph();
+    text_out.paragraphs.append(&mut text_out.notes);
+    Ok(text_out.paragraphs)
+}
+
+/// Paragraphs of an RTF document as they are read
+#[derive(Default)]
+struct RtfText {
+    paragraphs: Vec<SpecParagraph>,
+    notes: Vec<SpecParagraph>,  // Footnotes waiting for the paragraph that cites them to end
+    current: String,
+    note: String,
+    in_table: bool,  // The current paragraph is in a table cell, as marked by \intbl
+}
+
+impl RtfText {
+    /// Add a character to the paragraph or footnote being read, where '\n' ends it
+    fn push(&mut self, c: char, footnote: bool) {
+        match (c, footnote) {
+            ('\n', true) => self.end_note(),
+            ('\n', false) => self.end_paragraph(),
+            (c, true) => self.note.push(c),
+            (c, false) => self.current.push(c),
+        }
+    }
+
+    fn end_note(&mut self) {
+        self.notes.push(SpecParagraph::new(std::mem::take(&mut self.note), ParagraphOrigin::Footnote));
+    }
+
+    fn end_paragraph(&mut self) {
+        let origin = if self.in_table { ParagraphOrigin::TableCell } else { ParagraphOrigin::Body };
+        self.paragraphs.push(SpecParagraph::new(std::mem::take(&mut self.current), origin));
+        self.paragraphs.append(&mut self.notes);
+    }
+}
+
+/// Strip the Markdown markup a line may carry: heading marks, bullets, quotes and emphasis
+fn strip_markdown(line: &str) -> String {
+    let line = line.trim();
+    let line = line.trim_start_matches('#').trim_start();
+    let line = ["- ", "* ", "+ ", "> "].iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .unwrap_or(line);
+    line.replace("**", "").replace("__", "").replace('`', "")
+}
+
+/// Paragraphs of a plain text or Markdown file. Paragraphs are separated by blank lines, and lines
+/// wrapped within a paragraph are joined; a file without blank lines has a paragraph on each line.
+/// Files that are not UTF-8 are read as Windows-1252.
+fn text_paragraphs(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read(path)
+        .context("Failed to read text file")?;
+    let content = match String::from_utf8(content) {
+        Ok(content) => content,
+        Err(e) => e.into_bytes().into_iter().map(cp1252_char).collect(),
+    };
+    let content = content.trim_start_matches('\u{feff}');
+    let blank_separated = content.lines().any(|line| line.trim().is_empty());
+
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    for line in content.lines() {
+        // Headings stand alone even when text follows on the next line
+        let heading = line.trim_start().starts_with('#');
+        if line.trim().is_empty() || !blank_separated || heading {
+            paragraphs.push(current.join(" "));
+            current.clear();
+        }
+        current.push(strip_markdown(line));
+        if heading {
+            paragraphs.push(current.join(" "));
+            current.clear();
+        }
+    }
+    paragraphs.push(current.join(" "));
+    Ok(paragraphs)
+}
+
+/// Paragraphs of a PDF's text layer, one for each text block. A word hyphenated across
+/// a line break is joined up again. Scanned PDFs without a text layer cannot be read.
+fn pdf_paragraphs(path: &Path) -> Result<Vec<String>> {
+    let path = path.to_str().context("Invalid PDF path")?;
+    let doc = Document::open(path)
+        .context("Failed to open PDF file")?;
+    let page_count = doc.page_count()
+        .context("Failed to get page count")?;
+
+    let mut paragraphs = Vec::new();
+    for page_num in 0..page_count {
+        let page = doc.load_page(page_num)
+            .context("Failed to load PDF page")?;
+        let text_page = page.to_text_page(TextPageOptions::empty())
+            .context("Failed to extract structured text")?;
+
+        for block in text_page.blocks() {
+            let mut paragraph = String::new();
+            for line in block.lines() {
+                let line_text: String = line.chars().filter_map(|c| c.char()).collect();
+                let line_text = line_text.trim();
+                let hyphenated = paragraph.ends_with('-') && line_text.starts_with(|c: char| c.is_lowercase());
+                if hyphenated {
+                    paragraph.pop();
+                } else if !paragraph.is_empty() {
+                    paragraph.push(' ');
+                }
+                paragraph.push_str(line_text);
+            }
+            paragraphs.push(paragraph);
+        }
+    }
+
+    if paragraphs.iter().all(|paragraph| paragraph.trim().is_empty()) {
+        bail!("PDF specification has no text layer");
+    }
+    Ok(paragraphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect_bytes(name: &str, data: &[u8]) -> Result<SpecSource> {
+        let path = std::env::temp_dir().join(format!("spec_detect_{}_{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        let source = SpecSource::detect(&path);
+        std::fs::remove_file(&path).unwrap();
+        source
+    }
+
+    #[test]
+    fn text_is_read_as_text() {
+        assert!(matches!(detect_bytes("plain.txt", b"The housing 102\r\n\tand a lid 104.\n"), Ok(SpecSource::Text(_))));
+        assert!(matches!(detect_bytes("utf8.txt", "Caf\u{e9} arm 106 \u{2014} end".as_bytes()), Ok(SpecSource::Text(_))));
+    }
+
+    #[test]
+    fn legacy_word_documents_are_rejected() {
+        let mut doc = OLE_MAGIC.to_vec();
+        doc.extend_from_slice(&[0xA1, 0xB1, 0x1A, 0xE1, 0, 0]);
+        let error = detect_bytes("legacy.doc", &doc).unwrap_err();
+        assert!(error.to_string().contains("Unsupported specification format"), "{}", error);
+    }
+
+    #[test]
+    fn binary_files_are_rejected() {
+        assert!(detect_bytes("image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_err());
+        assert!(detect_bytes("nul.bin", b"arm 106\0\0").is_err());
+    }
+
+    #[test]
+    fn windows_1252_text_is_read_as_text() {
+        let path = std::env::temp_dir().join(format!("spec_cp1252_{}.txt", std::process::id()));
+        std::fs::write(&path, b"caf\xe9 arm 106 \x96 end").unwrap();
+        let source = SpecSource::detect(&path).unwrap();
+        let paragraphs = source.paragraphs(RevisionView::Final).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(source, SpecSource::Text(_)));
+        assert_eq!(paragraphs, vec![SpecParagraph::new("caf\u{e9} arm 106 \u{2013} end", ParagraphOrigin::Body)]);
+    }
+
+    fn read_spec(name: &str, data: &[u8]) -> Vec<(String, ParagraphOrigin)> {
+        let path = std::env::temp_dir().join(format!("spec_read_{}_{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        let paragraphs = SpecSource::detect(&path).unwrap().paragraphs(RevisionView::Final).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        paragraphs.into_iter().map(|paragraph| (paragraph.text, paragraph.origin)).collect()
+    }
+
+    fn owned(paragraphs: &[(&str, ParagraphOrigin)]) -> Vec<(String, ParagraphOrigin)> {
+        paragraphs.iter().map(|(text, origin)| (text.to_string(), *origin)).collect()
+    }
+
+    #[test]
+    fn rtf_text_notes_tables_and_deletions() {
+        let rtf = concat!(
+            "{\\rtf1\\ansi\\uc1 The arm 10\\u8211?12 caf\\'e9 pivots",
+            "{\\super\\chftn{\\footnote\\pard\\plain See lever 104.}} here.\\par\r\n",
+            "\\pard\\intbl pin 106\\cell\\row\r\n",
+            "\\pard {\\uc2 x\\u8212\\'97\\'97y}\\par\r\n",
+            "The lid {\\deleted 110}112.\\par}",
+        );
+        assert_eq!(read_spec("notes.rtf", rtf.as_bytes()), owned(&[
+            ("The arm 10\u{2013}12 caf\u{e9} pivots here.", ParagraphOrigin::Body),
+            ("See lever 104.", ParagraphOrigin::Footnote),
+            ("pin 106", ParagraphOrigin::TableCell),
+            ("x\u{2014}y", ParagraphOrigin::Body),
+            ("The lid 112.", ParagraphOrigin::Body),
+        ]));
+    }
+
+    #[test]
+    fn odt_text_notes_tables_and_spaces() {
+        let content = concat!(
+            "<office:document-content><office:body><office:text>",
+            "<text:tracked-changes><text:changed-region><text:deletion><text:p>gone 999</text:p></text:deletion></text:changed-region></text:tracked-changes>",
+            "<text:p>The arm<text:s text:c=\"3\"/>102 pivots",
+            "<text:note><text:note-citation>1</text:note-citation><text:note-body><text:p>See lever 104.</text:p></text:note-body></text:note>",
+            " here.</text:p>",
+            "<table:table><table:table-row><table:table-cell><text:p>pin 106</text:p></table:table-cell></table:table-row></table:table>",
+            "<text:h>Lid<office:annotation><text:p>ask about 777</text:p></office:annotation> 108</text:h>",
+            "</office:text></office:body></office:document-content>",
+        );
+        let mut odt = std::io::Cursor::new(Vec::new());
+        let mut archive = zip::ZipWriter::new(&mut odt);
+        archive.start_file("content.xml", zip::write::SimpleFileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut archive, content.as_bytes()).unwrap();
+        archive.finish().unwrap();
+        assert_eq!(read_spec("notes.odt", odt.get_ref()), owned(&[
+            ("The arm   102 pivots here.", ParagraphOrigin::Body),
+            ("See lever 104.", ParagraphOrigin::Footnote),
+            ("pin 106", ParagraphOrigin::TableCell),
+            ("Lid 108", ParagraphOrigin::Body),
+        ]));
+    }
+
+    #[test]
+    fn only_control_bytes_are_binary() {
+        let dash = "\u{2014}".as_bytes();
+        assert!(!is_binary(&dash[..2]));
+        assert!(!is_binary(b"caf\xe9 arm 106"));
+        assert!(is_binary(b"\x01\x02"));
+        assert!(!is_binary(b"text\x0c next page"));
+    }
+}
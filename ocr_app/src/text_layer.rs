@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use image::{Rgb, RgbImage};
+use mupdf::{Document, TextPageOptions};
+
+use crate::{joins_figure_number, normalize_text, OcrResult, ResultSource};
+
+/// White margin painted around text-layer words before OCR, in pixels
+const MASK_PADDING: u32 = 2;
+
+/// A character of the text layer with its normalized corners, clockwise from the top-left
+type LayerChar = (char, [[f32; 2]; 4]);
+
+/// Read the words of a page's text layer as results normalized to the page.
+/// Pages whose text was outlined or scanned have no text layer and give no words.
+pub fn page_words(doc: &Document, page_num: i32) -> Result<Vec<OcrResult>> {
+    let page = doc.load_page(page_num)
+        .context("Failed to load PDF page")?;
+    let bounds = page.bounds()
+        .context("Failed to get page bounds")?;
+    let (width, height) = (bounds.x1 - bounds.x0, bounds.y1 - bounds.y0);
+    let normalize = |point: mupdf::Point| [(point.x - bounds.x0) / width, (point.y - bounds.y0) / height];
+
+    let text_page = page.to_text_page(TextPageOptions::empty())
+        .context("Failed to extract structured text")?;
+
+    let mut results = Vec::new();
+    let mut line_index = 0;
+    for block in text_page.blocks() {
+        for line in block.lines() {
+            // Split the line into words at whitespace
+            let mut words: Vec<Vec<LayerChar>> = vec![Vec::new()];
+            for text_char in line.chars() {
+                let Some(c) = text_char.char() else { continue };
+                if c.is_whitespace() {
+                    words.push(Vec::new());
+                    continue;
+                }
+                let quad = text_char.quad();
+                words.last_mut().unwrap().push((c, [normalize(quad.ul), normalize(quad.ur), normalize(quad.lr), normalize(quad.ll)]));
+            }
+
+            // Fonts without a Unicode mapping give garbled text, so those words are left to OCR
+            words.retain(|word| !word.is_empty() && word.iter().all(|(c, _)| *c != char::REPLACEMENT_CHARACTER && !c.is_control()));
+            if words.is_empty() {
+                continue;
+            }
+
+            let texts: Vec<String> = words.iter().map(|word| word.iter().map(|(c, _)| c).collect()).collect();
+            let line_text = normalize_text(&texts.join(" "));
+
+            let mut i = 0;
+            while i < words.len() {
+                // A FIG. keyword and the figure number after it make one word, as in OCR results
+                let end = if joins_figure_number(&texts[i], texts.get(i + 1).map(String::as_str)) { i + 2 } else { i + 1 };
+                let raw_word = texts[i..end].join(" ");
+                let chars: Vec<&LayerChar> = words[i..end].iter().flatten().collect();
+                i = end;
+
+                let text = normalize_text(&raw_word);
+                if text.is_empty() {
+                    continue;
+                }
+
+                // The word runs from the left edge of its first character to the right edge of its last
+                let (first, last) = (chars[0].1, chars[chars.len() - 1].1);
+                let mut bbox = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+                for [x, y] in chars.iter().flat_map(|(_, corners)| corners) {
+                    bbox = [bbox[0].min(*x), bbox[1].min(*y), bbox[2].max(*x), bbox[3].max(*y)];
+                }
+
+                results.push(OcrResult {
+                    text,
+                    bbox,
+                    quad: [first[0], last[1], last[2], first[3]],
+                    line: line_index,
+                    line_text: line_text.clone(),
+                    confidence: 1.0,
+                    label_fit: 1.0,
+                    source: ResultSource::TextLayer,
+                });
+            }
+            line_index += 1;
+        }
+    }
+
+    println!("[DEBUG] Text layer of page {}: {} words", page_num + 1, results.len());
+    Ok(results)
+}
+
+/// Paint the text-layer words of a rendered page white, so OCR only reads what the text layer lacks
+pub fn mask_words(img: &mut RgbImage, words: &[OcrResult]) {
+    let (width, height) = img.dimensions();
+    for word in words {
+        let [x1, y1, x2, y2] = word.bbox;
+        let x1 = ((x1 * width as f32).max(0.0) as u32).saturating_sub(MASK_PADDING);
+        let y1 = ((y1 * height as f32).max(0.0) as u32).saturating_sub(MASK_PADDING);
+        let x2 = ((x2 * width as f32).ceil().max(0.0) as u32 + MASK_PADDING).min(width);
+        let y2 = ((y2 * height as f32).ceil().max(0.0) as u32 + MASK_PADDING).min(height);
+        for y in y1..y2 {
+            for x in x1..x2 {
+                img.put_pixel(x, y, Rgb([255, 255, 255]));
+            }
+        }
+    }
+}
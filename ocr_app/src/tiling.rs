@@ -0,0 +1,193 @@
+use anyhow::{bail, Result};
+use image::{imageops, RgbImage};
+use ocrs::OcrEngine;
+
+use crate::{orientation, page_results, LabelGrammar, OcrResult};
+
+/// Overlap between neighbouring tiles in pixels, wider than a reference label at 300 DPI
+pub const DEFAULT_TILE_OVERLAP: u32 = 160;
+
+/// Smallest tile accepted; every tile is OCR'd in four orientations, so tiny tiles multiply the work
+pub const MIN_TILE_SIZE: u32 = 256;
+
+/// Detections from two tiles covering this much of the smaller box are the same word
+const DUPLICATE_OVERLAP: f32 = 0.5;
+
+/// Results of each tile, normalized to the tile
+type TileResults = Vec<(Tile, Vec<OcrResult>)>;
+
+/// A tile of the page, in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Offsets of the tiles along one side, spread evenly so the first and last tiles touch the edges
+fn tile_offsets(length: u32, tile_size: u32, overlap: u32) -> Vec<u32> {
+    if length <= tile_size {
+        return vec![0];
+    }
+
+    // Enough tiles that neighbours share at least `overlap` pixels
+    let step = tile_size - overlap;
+    let count = (length - overlap).div_ceil(step);
+    let span = (length - tile_size) as u64;
+    (0..count as u64)
+        .map(|i| (i * span / (count as u64 - 1)) as u32)
+        .collect()
+}
+
+/// Split a page into overlapping tiles no larger than `tile_size` on either side.
+/// Tiles smaller than `MIN_TILE_SIZE` are refused.
+pub fn tiles(width: u32, height: u32, tile_size: u32, overlap: u32) -> Result<Vec<Tile>> {
+    if tile_size < MIN_TILE_SIZE {
+        bail!("tile_size must be at least {}, got {}", MIN_TILE_SIZE, tile_size);
+    }
+    // Neighbours must still advance, so the overlap is at most half a tile
+    let overlap = overlap.min(tile_size / 2);
+
+    let mut tiles = Vec::new();
+    for &y in &tile_offsets(height, tile_size, overlap) {
+        for &x in &tile_offsets(width, tile_size, overlap) {
+            tiles.push(Tile { x, y, width: tile_size.min(width), height: tile_size.min(height) });
+        }
+    }
+    Ok(tiles)
+}
+
+impl Tile {
+    /// Map a normalized point on the tile to a normalized point on the page
+    fn point_to_page(&self, [x, y]: [f32; 2], page_width: u32, page_height: u32) -> [f32; 2] {
+        [
+            (self.x as f32 + x * self.width as f32) / page_width as f32,
+            (self.y as f32 + y * self.height as f32) / page_height as f32,
+        ]
+    }
+
+    /// Pixels between a page box and the nearest tile edge that lies inside the page.
+    /// Words close to a seam may be cut off, so the copy further from it is kept.
+    fn seam_margin(&self, bbox: [f32; 4], page_width: u32, page_height: u32) -> f32 {
+        let (width, height) = (page_width as f32, page_height as f32);
+        let mut margin = f32::MAX;
+        if self.x > 0 {
+            margin = margin.min(bbox[0] * width - self.x as f32);
+        }
+        if self.y > 0 {
+            margin = margin.min(bbox[1] * height - self.y as f32);
+        }
+        if self.x + self.width < page_width {
+            margin = margin.min((self.x + self.width) as f32 - bbox[2] * width);
+        }
+        if self.y + self.height < page_height {
+            margin = margin.min((self.y + self.height) as f32 - bbox[3] * height);
+        }
+        margin
+    }
+}
+
+/// Fraction of the smaller box covered by the intersection of two boxes
+fn overlap_fraction(a: [f32; 4], b: [f32; 4]) -> f32 {
+    let intersection = (a[2].min(b[2]) - a[0].max(b[0])).max(0.0) * (a[3].min(b[3]) - a[1].max(b[1])).max(0.0);
+    let smaller = ((a[2] - a[0]) * (a[3] - a[1])).min((b[2] - b[0]) * (b[3] - b[1]));
+    if smaller <= 0.0 {
+        return 0.0;
+    }
+    intersection / smaller
+}
+
+/// Move tile results into page coordinates and drop words read twice where tiles overlap
+fn merge_tiles(tile_results: TileResults, page_width: u32, page_height: u32) -> Vec<OcrResult> {
+    // Each candidate keeps its tile and reading order so the output stays in page order
+    let mut candidates = Vec::new();
+    let mut next_line = 0;
+    for (tile_index, (tile, results)) in tile_results.into_iter().enumerate() {
+        // Line indexes restart in every tile, so shift them past those of earlier tiles
+        let line_offset = next_line;
+        next_line += results.iter().map(|result| result.line + 1).max().unwrap_or(0);
+        for result in results {
+            let [x1, y1] = tile.point_to_page([result.bbox[0], result.bbox[1]], page_width, page_height);
+            let [x2, y2] = tile.point_to_page([result.bbox[2], result.bbox[3]], page_width, page_height);
+            let bbox = [x1, y1, x2, y2];
+            let margin = tile.seam_margin(bbox, page_width, page_height);
+            let quad = result.quad.map(|corner| tile.point_to_page(corner, page_width, page_height));
+            let order = candidates.len();
+            candidates.push((tile_index, order, margin, OcrResult { bbox, quad, line: result.line + line_offset, ..result }));
+        }
+    }
+
+    // Copies furthest from a seam are seen whole, so they win over overlapping copies from other tiles
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+    let mut kept: Vec<(usize, usize, f32, OcrResult)> = Vec::new();
+    let mut dropped = 0;
+    for candidate in candidates {
+        let duplicate = kept.iter().any(|(tile_index, _, _, result)| {
+            *tile_index != candidate.0 && overlap_fraction(result.bbox, candidate.3.bbox) > DUPLICATE_OVERLAP
+        });
+        if duplicate {
+            dropped += 1;
+        } else {
+            kept.push(candidate);
+        }
+    }
+    println!("[DEBUG] Dropped {} words read again across tile seams", dropped);
+
+    kept.sort_by_key(|(_, order, _, _)| *order);
+    kept.into_iter().map(|(_, _, _, result)| result).collect()
+}
+
+/// OCR a large page in overlapping tiles and return results in whole-page normalized coordinates.
+/// Tiles are recognized one at a time. The sheet orientation is chosen for the page as a whole,
+/// by how well all of its tiles read together, and only its tiles are turned into results.
+pub fn process_tiled(engine: &OcrEngine, img: &RgbImage, grammar: &LabelGrammar, tile_size: u32, overlap: u32) -> Result<Vec<OcrResult>> {
+    let (page_width, page_height) = img.dimensions();
+    let tiles = tiles(page_width, page_height, tile_size, overlap)?;
+    println!("[DEBUG] OCR of a {}x{} page in {} tiles of up to {} pixels", page_width, page_height, tiles.len(), tile_size);
+
+    let matcher = orientation::LabelMatcher::new(grammar)?;
+    let mut best: Option<(f32, Vec<orientation::RecognizedPage>)> = None;
+    for rotation in orientation::ROTATIONS {
+        let mut score = 0.0;
+        let mut pages = Vec::new();
+        for tile in &tiles {
+            let crop = imageops::crop_imm(img, tile.x, tile.y, tile.width, tile.height).to_image();
+            let page = orientation::recognize(engine, &crop, rotation)?;
+            score += orientation::readability(&page.line_texts, &matcher);
+            pages.push(page);
+        }
+        println!("[DEBUG] Orientation {}: score {:.1}", rotation, score);
+
+        // The upright orientation wins ties, as for whole pages
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, pages));
+        }
+    }
+
+    let (_, pages) = best.unwrap();
+    let mut tile_results = Vec::new();
+    for (tile, page) in tiles.into_iter().zip(&pages) {
+        tile_results.push((tile, page_results(engine, page, grammar)?));
+    }
+    Ok(merge_tiles(tile_results, page_width, page_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_tiles_are_refused() {
+        assert!(tiles(1024, 1024, 0, 0).is_err());
+        assert!(tiles(1024, 1024, MIN_TILE_SIZE - 1, 0).is_err());
+        assert_eq!(tiles(1024, 1024, MIN_TILE_SIZE, 0).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn tiles_cover_the_page() {
+        let tiles = tiles(4961, 3508, 2048, 160).unwrap();
+        assert!(tiles.iter().any(|tile| tile.x == 0 && tile.y == 0));
+        assert!(tiles.iter().any(|tile| tile.x + tile.width == 4961 && tile.y + tile.height == 3508));
+    }
+}